@@ -0,0 +1,219 @@
+//! Plays the game in a terminal with truecolor support, for trying out rule changes without
+//! the board. Every character shows two pixels of a downscaled field with the upper half
+//! block, foreground for the top and background for the bottom pixel.
+//!
+//! Keys: A/D, J/L, arrows and 4/6 steer the players, Space starts the next round, Q quits.
+//! Terminals only report key presses, a key counts as held for `HOLD` after the last press
+//! (or auto-repeat) of it.
+use std::env;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use embedded_graphics::{
+    Drawing,
+    drawable::Pixel,
+};
+use termion::{clear, color, cursor};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+
+use curve::{HEIGHT, WIDTH};
+use curve::game::{Game, GameState};
+use curve::map::Map;
+use curve::player::PlayerInput;
+use curve::random::XorShift;
+use curve::scoring::scoring_rule;
+use curve::color::GameColor;
+use curve::settings::{Settings, MAX_PLAYERS};
+use curve::theme::theme;
+use curve::trace::TracePool;
+
+/// Left and right key of every player.
+const KEYS: [(Key, Key); MAX_PLAYERS] = [
+    (Key::Char('a'), Key::Char('d')),
+    (Key::Char('j'), Key::Char('l')),
+    (Key::Left, Key::Right),
+    (Key::Char('4'), Key::Char('6')),
+];
+/// Pixels of the field per terminal column.
+const SCALE: usize = 4;
+const COLUMNS: usize = WIDTH / SCALE;
+const ROWS: usize = HEIGHT / SCALE / 2;
+const HOLD: Duration = Duration::from_millis(200);
+/// The board ticks with 100 Hz.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Framebuffer that is printed as half blocks, only the changed cells are sent.
+struct TerminalDisplay {
+    pixels: Vec<u32>,
+    shown: Vec<Option<(u32, u32)>>,
+    background: u32,
+}
+
+impl TerminalDisplay {
+    fn new(background: GameColor) -> Self {
+        Self {
+            pixels: vec![background.value; WIDTH * HEIGHT],
+            shown: vec![None; COLUMNS * ROWS],
+            background: background.value,
+        }
+    }
+
+    fn clear(&mut self) {
+        for p in &mut self.pixels {
+            *p = self.background;
+        }
+    }
+
+    /// Color of a downscaled pixel, anything drawn in the block wins over the background.
+    fn sample(&self, x: usize, y: usize) -> u32 {
+        for dy in 0..SCALE {
+            for dx in 0..SCALE {
+                let p = self.pixels[(y * SCALE + dy) * WIDTH + x * SCALE + dx];
+                if p != self.background {
+                    return p;
+                }
+            }
+        }
+        self.background
+    }
+
+    fn flush<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        for row in 0..ROWS {
+            for col in 0..COLUMNS {
+                let cell = (self.sample(col, 2 * row), self.sample(col, 2 * row + 1));
+                if self.shown[row * COLUMNS + col] == Some(cell) {
+                    continue;
+                }
+                self.shown[row * COLUMNS + col] = Some(cell);
+                write!(out, "{}{}{}\u{2580}", cursor::Goto(col as u16 + 1, row as u16 + 1),
+                       color::Fg(rgb(cell.0)), color::Bg(rgb(cell.1)))?;
+            }
+        }
+        write!(out, "{}{}", color::Fg(color::Reset), color::Bg(color::Reset))?;
+        out.flush()
+    }
+}
+
+impl Drawing<GameColor> for TerminalDisplay {
+    fn draw<T>(&mut self, item_pixels: T)
+    where
+        T: Iterator<Item = Pixel<GameColor>>
+    {
+        for Pixel(coord, color) in item_pixels {
+            if coord.0 as usize >= WIDTH || coord.1 as usize >= HEIGHT {
+                continue;
+            }
+            self.pixels[coord.1 as usize * WIDTH + coord.0 as usize] = color.value;
+        }
+    }
+}
+
+fn rgb(value: u32) -> color::Rgb {
+    let c = GameColor{value};
+    color::Rgb(c.r(), c.g(), c.b())
+}
+
+/// Keyboard as the input source, replaces the touch regions of the board.
+struct Keyboard {
+    pressed: [(Option<Instant>, Option<Instant>); MAX_PLAYERS],
+}
+
+impl Keyboard {
+    fn new() -> Self {
+        Self {
+            pressed: [(None, None); MAX_PLAYERS],
+        }
+    }
+
+    fn press(&mut self, key: Key, now: Instant) {
+        for (i, (left, right)) in KEYS.iter().enumerate() {
+            if key == *left {
+                self.pressed[i].0 = Some(now);
+            } else if key == *right {
+                self.pressed[i].1 = Some(now);
+            }
+        }
+    }
+
+    fn inputs(&self, now: Instant) -> Vec<PlayerInput> {
+        let held = |t: Option<Instant>| t.map_or(false, |t| now - t < HOLD);
+        self.pressed.iter().map(|(left, right)| match (held(*left), held(*right)) {
+            (true, true) => PlayerInput::Both,
+            (true, false) => PlayerInput::Left,
+            (false, true) => PlayerInput::Right,
+            (false, false) => PlayerInput::None,
+        }).collect()
+    }
+}
+
+fn main() -> io::Result<()> {
+    let players = env::args().nth(1)
+                             .and_then(|a| a.parse().ok())
+                             .unwrap_or(2_usize)
+                             .max(2)
+                             .min(MAX_PLAYERS);
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(1);
+    let mut rng = XorShift::new(seed);
+    let settings = Settings::default();
+    let theme = theme(settings.theme as usize);
+    let mut traces = TracePool::new();
+    let mut game = Game::new(&theme.players[..players], settings, scoring_rule(0),
+                             Map::builtin(0), &mut rng, &mut traces);
+    let mut display = TerminalDisplay::new(theme.background);
+    let mut keyboard = Keyboard::new();
+
+    let mut out = io::stdout().into_raw_mode()?;
+    let mut keys = termion::async_stdin().keys();
+    write!(out, "{}{}", clear::All, cursor::Hide)?;
+
+    'session: loop {
+        game.new_game(&mut rng);
+        display.clear();
+        game.draw_arena(&mut display);
+
+        let mut last = Instant::now();
+        loop {
+            thread::sleep(TICK);
+            let now = Instant::now();
+            while let Some(Ok(key)) = keys.next() {
+                if key == Key::Char('q') || key == Key::Esc {
+                    break 'session;
+                }
+                keyboard.press(key, now);
+            }
+            // same pace as the firmware, a step every 3 ticks
+            let ticks = ((now - last).as_millis() / TICK.as_millis()) as usize;
+            if ticks < 3 {
+                continue;
+            }
+            last = now;
+            let inputs = keyboard.inputs(now);
+            let state = game.step(&mut rng, &mut display, &inputs, ticks);
+            display.flush(&mut out)?;
+            if let GameState::Finished = state {
+                break;
+            }
+        }
+
+        let scores: Vec<String> = game.players.iter()
+                                              .map(|p| format!("{}: {}", p.name, p.score))
+                                              .collect();
+        write!(out, "{}{}  {}  (Space: next round, Q: quit)", cursor::Goto(1, ROWS as u16 + 2),
+               game.winner_message(), scores.join("  "))?;
+        out.flush()?;
+        loop {
+            thread::sleep(TICK);
+            match keys.next() {
+                Some(Ok(Key::Char(' '))) => break,
+                Some(Ok(Key::Char('q'))) | Some(Ok(Key::Esc)) => break 'session,
+                _ => {},
+            }
+        }
+        write!(out, "{}{}", cursor::Goto(1, ROWS as u16 + 2), clear::CurrentLine)?;
+    }
+    write!(out, "{}{}{}", cursor::Goto(1, ROWS as u16 + 3), cursor::Show, color::Fg(color::Reset))?;
+    out.flush()
+}
@@ -0,0 +1,161 @@
+use crate::{HEIGHT, WIDTH};
+
+use embedded_graphics::{
+    prelude::*,
+    coord::Coord,
+    primitives::Rect,
+};
+
+use crate::color::GameColor;
+use crate::theme::Theme;
+use crate::geometry::AABBox;
+
+use crate::player::{PAD_LEFT, PAD_RIGHT, PAD_BOTTOM, PAD_TOP};
+
+// the outline blinks for this many ticks before it shrinks
+const WARN_TICKS: isize = 100*2;
+const BLINK_TICKS: isize = 25;
+const MIN_SIZE: i32 = 60;
+
+/// Battle royale mode: the border closes in after a grace period.
+#[derive(Copy, Clone, Debug)]
+pub struct Shrink {
+    /// Ticks before the first shrink.
+    pub grace: u32,
+    /// Ticks between two shrinks.
+    pub interval: u32,
+    /// Pixels the border moves inwards on every side.
+    pub step: u32,
+}
+
+pub struct Border {
+    pub top_left : Coord,
+    pub bottom_right : Coord,
+    pub active : bool,
+    pub drawn : bool,
+    shrink: Option<Shrink>,
+    tt_shrink: isize,
+    warning: bool,
+    erase: Option<(Coord, Coord)>,
+}
+
+impl Border {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_shrink(shrink: Shrink) -> Self {
+        let mut border = Self::default();
+        border.shrink = Some(shrink);
+        border.reset();
+        border
+    }
+
+    pub fn reset(&mut self) {
+        let shrink = self.shrink;
+        *self = Self::default();
+        self.shrink = shrink;
+        if let Some(s) = shrink {
+            self.active = true;
+            self.tt_shrink = s.grace as isize;
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        // a closing border can't be switched off
+        if self.shrink.is_some() { return; }
+        self.active = !self.active;
+        self.drawn = false;
+    }
+
+    /// Area a curve or buff has to stay in.
+    pub fn bounds(&self) -> AABBox {
+        AABBox::new(self.top_left, self.bottom_right)
+    }
+
+    /// Whether the border blinks because it is about to shrink.
+    pub fn is_warning(&self) -> bool {
+        self.warning
+    }
+
+    /// Whether moving every side inwards by `step` leaves at least `MIN_SIZE`.
+    fn can_shrink(&self, step: u32) -> bool {
+        let step = step as i32;
+        let width = self.bottom_right[0] - self.top_left[0];
+        let height = self.bottom_right[1] - self.top_left[1];
+        width - 2 * step >= MIN_SIZE && height - 2 * step >= MIN_SIZE
+    }
+
+    /// Advances the shrink timer. Returns `true` if the border has just moved. Once the border
+    /// can't get any smaller, the timer stops and the border doesn't warn anymore.
+    pub fn update(&mut self, dt: usize) -> bool {
+        let shrink = match self.shrink {
+            Some(s) => s,
+            None => return false,
+        };
+        if !self.can_shrink(shrink.step) {
+            if self.warning {
+                self.warning = false;
+                self.drawn = false;
+            }
+            return false;
+        }
+        let old_phase = self.tt_shrink / BLINK_TICKS;
+        self.tt_shrink -= dt as isize;
+        let warning = self.tt_shrink < WARN_TICKS;
+        if warning != self.warning || (warning && old_phase != self.tt_shrink / BLINK_TICKS) {
+            self.warning = warning;
+            self.drawn = false;
+        }
+        if self.tt_shrink >= 0 { return false; }
+
+        self.tt_shrink = shrink.interval as isize;
+        self.warning = false;
+        let step = shrink.step as i32;
+        self.erase = Some((self.top_left, self.bottom_right));
+        self.top_left = Coord::new(self.top_left[0] + step, self.top_left[1] + step);
+        self.bottom_right = Coord::new(self.bottom_right[0] - step, self.bottom_right[1] - step);
+        self.drawn = false;
+        true
+    }
+ 
+    pub fn draw<D: Drawing<GameColor>>(&mut self, display: &mut D, theme: &Theme){
+        if let Some((top_left, bottom_right)) = self.erase.take() {
+            display.draw(Rect::new(top_left, bottom_right)
+                .with_stroke(Some(theme.background))
+                .with_stroke_width(1)
+                .into_iter() );
+        }
+        if self.drawn { return; }
+        if self.active {
+            let blink = self.warning && (self.tt_shrink / BLINK_TICKS) % 2 == 0;
+            let color = if blink { theme.border_warning } else { theme.border };
+            display.draw(Rect::new(self.top_left, self.bottom_right)
+                .with_stroke(Some(color))
+                .with_stroke_width(1)
+                .into_iter() );
+        } else {
+            display.draw(Rect::new(self.top_left, self.bottom_right)
+                .with_stroke(Some(theme.background))
+                .with_stroke_width(1)
+                .into_iter() );
+        }
+        self.drawn = true;
+    }
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Self {
+            top_left : Coord::new(PAD_LEFT as i32, PAD_TOP as i32),
+            bottom_right : Coord::new((WIDTH-1) as i32 - PAD_RIGHT as i32, 
+                                    (HEIGHT-1) as i32 - PAD_BOTTOM as i32),
+            active : false,
+            drawn : false,
+            shrink: None,
+            tt_shrink: 0,
+            warning: false,
+            erase: None,
+        }
+    }
+}
\ No newline at end of file
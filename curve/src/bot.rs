@@ -0,0 +1,126 @@
+//! Computer players for demo rounds. A bot probes a short path for going straight and for
+//! turning either way, and takes the one that stays clear of everything the longest.
+use core::f32::consts::PI;
+use heapless::Vec;
+use heapless::consts::U4;
+
+use crate::game::Game;
+use crate::geometry::Vector2D;
+use crate::map::EdgeBehavior;
+use crate::player::{PlayerInput, FIELD_LEFT, FIELD_RIGHT, FIELD_TOP, FIELD_BOTTOM};
+
+/// Number of probe points per choice.
+const LOOKAHEAD: usize = 12;
+/// Distance between probe points, a curve moves about a pixel per step.
+const PROBE_STEP: f32 = 5_f32;
+/// A turning probe turns for this many points and then goes straight, turning all the way
+/// would just circle in free space.
+const TURN_PROBES: usize = 4;
+/// Same turn rate as `Curve::act` without a handicap.
+const TURN: f32 = 5_f32 * PI / 180.0;
+/// Free space a probe point needs on top of the radius.
+const MARGIN: f32 = 3_f32;
+/// Length of the own trace behind the head that the probes ignore, they start right on it.
+const OWN_TRACE_GRACE: f32 = 20_f32;
+
+/// Inputs for all players of the game controlled by bots.
+pub fn bot_inputs(game: &Game) -> Vec<PlayerInput, U4> {
+    (0..game.players.len()).map(|i| bot_input(game, i)).collect()
+}
+
+/// Chooses the input for one player, going straight unless turning keeps it alive longer.
+pub fn bot_input(game: &Game, player: usize) -> PlayerInput {
+    if game.players[player].lost {
+        return PlayerInput::None;
+    }
+    let turn = TURN * game.players[player].curve.handicap().turn;
+    let mut best = (PlayerInput::None, free_probes(game, player, 0_f32));
+    for (input, turn) in [(PlayerInput::Left, -turn), (PlayerInput::Right, turn)].iter() {
+        let free = free_probes(game, player, *turn);
+        if free > best.1 {
+            best = (*input, free);
+        }
+    }
+    best.0
+}
+
+/// Number of probe points along a path with `turn` per pixel before one is blocked.
+fn free_probes(game: &Game, player: usize, turn: f32) -> usize {
+    let curve = &game.players[player].curve;
+    let clearance = curve.effective_radius() + MARGIN;
+    let mut pos = curve.pos();
+    let mut direction = curve.direction();
+    for i in 0..LOOKAHEAD {
+        if i < TURN_PROBES {
+            direction = direction.rotate(turn * PROBE_STEP);
+        }
+        pos = wrap(game, pos + direction * PROBE_STEP);
+        if blocked(game, player, pos, clearance) {
+            return i;
+        }
+    }
+    LOOKAHEAD
+}
+
+/// Moves a probe that left the field through a wrapping edge back in on the other side.
+fn wrap(game: &Game, mut pos: Vector2D) -> Vector2D {
+    let edges = &game.map().edges;
+    let (width, height) = (FIELD_RIGHT - FIELD_LEFT, FIELD_BOTTOM - FIELD_TOP);
+    if pos.x < FIELD_LEFT && edges.left == EdgeBehavior::Wrap {
+        pos.x += width;
+    } else if pos.x > FIELD_RIGHT && edges.right == EdgeBehavior::Wrap {
+        pos.x -= width;
+    }
+    if pos.y < FIELD_TOP && edges.top == EdgeBehavior::Wrap {
+        pos.y += height;
+    } else if pos.y > FIELD_BOTTOM && edges.bottom == EdgeBehavior::Wrap {
+        pos.y -= height;
+    }
+    pos
+}
+
+/// Whether anything deadly is closer than `clearance` to `pos`. Bouncing edges count as walls,
+/// a bot doesn't plan a bounce.
+fn blocked(game: &Game, player: usize, pos: Vector2D, clearance: f32) -> bool {
+    let edges = &game.map().edges;
+    let near_edge = [
+        (edges.left, pos.x - FIELD_LEFT),
+        (edges.right, FIELD_RIGHT - pos.x),
+        (edges.top, pos.y - FIELD_TOP),
+        (edges.bottom, FIELD_BOTTOM - pos.y),
+    ];
+    if near_edge.iter().any(|(e, d)| *e != EdgeBehavior::Wrap && *d < clearance) {
+        return true;
+    }
+    let border = game.border();
+    if border.active {
+        let b = border.bounds();
+        let inside = (pos.x - b.top_left[0] as f32)
+                         .min(b.bottom_right[0] as f32 - pos.x)
+                         .min(pos.y - b.top_left[1] as f32)
+                         .min(b.bottom_right[1] as f32 - pos.y);
+        if inside < clearance {
+            return true;
+        }
+    }
+    if game.map().distance(pos) < clearance {
+        return true;
+    }
+    for (i, p) in game.players.iter().enumerate() {
+        // the newest segments come first to skip the own ones at the head
+        let mut behind = 0_f32;
+        for seg in p.curve.trace().iter().rev() {
+            if i == player && behind < OWN_TRACE_GRACE {
+                behind += seg.start.distance(seg.end);
+                continue;
+            }
+            if pos.distance_to_segment(seg.start, seg.end) < clearance + seg.radius {
+                return true;
+            }
+        }
+        if i != player && !p.lost && pos.distance(p.curve.pos()) < clearance + PROBE_STEP {
+            return true;
+        }
+    }
+    false
+}
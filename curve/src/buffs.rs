@@ -1,15 +1,14 @@
-use alloc::vec::Vec;
 use crate::player::Curve;
 use crate::game::Player;
-use crate::display::{
-    GameColor
-};
+use crate::color::GameColor;
 use embedded_graphics::{
     coord::Coord,
 };
 use core::mem;
 use crate::geometry::ImgIterator;
 use crate::border::Border;
+use crate::settings::MAX_PLAYERS;
+use crate::theme::{Palette, next_player_color};
 
 const IMG_FAST: [u8; 10*10*3] = *include_bytes!("fast.data");
 const IMG_CLEAR: [u8; 10*10*3] = *include_bytes!("clear.data");
@@ -33,6 +32,8 @@ pub trait Buff {
     fn apply_player(&self, _player: &mut Curve, _collector: bool) {}
     fn apply_border(&self, _border: &mut Border) {}
     fn clear_screen(&self) -> bool { false }
+    /// Whether the traces change their color, the field has to be repainted.
+    fn recolors_traces(&self) -> bool { false }
     fn draw(&self) -> ImgIterator;
     fn aabb(&self) -> (Coord, Coord);
     fn get_pos(&self) -> Coord;
@@ -42,7 +43,8 @@ pub struct PlayerBuff {
     pub timeout: u32,
     pub change_rotation: fn(u32, f32) -> f32,
     pub change_speed: fn(u32, f32) -> f32,
-    pub change_color: fn(u32, GameColor) -> GameColor,
+    /// Gets the colors of all curves, see `Theme::player_colors`.
+    pub change_color: fn(u32, GameColor, &Palette) -> GameColor,
     pub change_radius: fn(u32, f32) -> f32,
 }
 
@@ -63,7 +65,7 @@ impl FastPlayerBuffSprite {
 impl Buff for FastPlayerBuffSprite {
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(_time: u32, rotation: f32) -> f32 {rotation}
             fn change_speed(_time: u32, speed: f32) -> f32 {speed + 1.0}
             fn change_radius(_time: u32, r: f32) -> f32 {r}
@@ -144,7 +146,7 @@ impl Buff for ChangeDirBuffSprite {
 
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if !collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(_time: u32, rotation: f32) -> f32 {360_f32-rotation}
             fn change_speed(_time: u32, speed: f32) -> f32 {speed}
             fn change_radius(_time: u32, r: f32) -> f32 {r}
@@ -188,7 +190,7 @@ impl SlowBuffSprite {
 impl Buff for SlowBuffSprite {
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(_time: u32, rotation: f32) -> f32 {rotation}
             fn change_speed(_time: u32, speed: f32) -> f32 {speed * 0.5}
             fn change_radius(_time: u32, r: f32) -> f32 {r}
@@ -232,7 +234,7 @@ impl BigBuffSprite {
 impl Buff for BigBuffSprite {
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(_time: u32, rotation: f32) -> f32 {rotation}
             fn change_speed(_time: u32, speed: f32) -> f32 {speed}
             fn change_radius(_time: u32, r: f32) -> f32 {r * 1.5}
@@ -276,7 +278,7 @@ impl SmallBuffSprite {
 impl Buff for SmallBuffSprite {
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(_time: u32, rotation: f32) -> f32 {rotation}
             fn change_speed(_time: u32, speed: f32) -> f32 {speed}
             fn change_radius(_time: u32, r: f32) -> f32 {r * 0.5}
@@ -319,12 +321,8 @@ impl ColorBuffSprite {
 
 impl Buff for ColorBuffSprite {
     fn apply_player(&self, player: &mut Curve, _collector: bool) {
-        fn change_color(_time: u32, mut color: GameColor) -> GameColor {
-            if color.value == 0xFF_0000 { color.value = 0xFF_FF00; }
-            else if color.value == 0xFF_FF00 { color.value = 0x00_00FF; }
-            else if color.value == 0x00_00FF { color.value = 0x00_FF00; }
-            else { color.value = 0xFF_FF00; }
-            color
+        fn change_color(_time: u32, color: GameColor, palette: &Palette) -> GameColor {
+            next_player_color(palette, color)
         }
         fn change_rotation(_time: u32, rotation: f32) -> f32 {rotation}
         fn change_speed(_time: u32, speed: f32) -> f32 {speed}
@@ -368,8 +366,7 @@ impl BorderBuffSprite {
 impl Buff for BorderBuffSprite {
 
     fn apply_border(&self, border: &mut Border) {
-        border.active = !border.active;
-        border.drawn = false;
+        border.toggle();
     }
 
     fn draw(&self) -> ImgIterator {
@@ -401,7 +398,7 @@ impl DrunkenBuffSprite {
 impl Buff for DrunkenBuffSprite {
     fn apply_player(&self, player: &mut Curve, collector: bool) {
         if !collector {
-            fn change_color(_time: u32, color: GameColor) -> GameColor {color}
+            fn change_color(_time: u32, color: GameColor, _palette: &Palette) -> GameColor {color}
             fn change_rotation(time: u32, rotation: f32) -> f32 {
                 if time % 100 < 50 {
                     rotation + 2_f32
@@ -448,29 +445,30 @@ impl SwapBuffSprite {
 
 impl Buff for SwapBuffSprite {
     fn apply_players(&self, players: &mut [Player], _collector_id: usize) {
-        let mut players_active: Vec<&mut Player> = players.iter_mut().filter(|p| !p.lost).collect();
-        if players_active.len() < 2 { return; }
-        let mut acc_curve = Curve::default();
-        
-        let mut curve_colors: Vec<GameColor> = Vec::new();
-        for p in &players_active {
-            curve_colors.push(p.curve.color);
+        let mut active = [0; MAX_PLAYERS];
+        let mut n = 0;
+        for (i, p) in players.iter().enumerate().filter(|(_, p)| !p.lost).take(MAX_PLAYERS) {
+            active[n] = i;
+            n += 1;
         }
+        if n < 2 { return; }
 
-        for i in 0..players_active.len()-1 {
-            mem::swap(&mut players_active[i+1].curve, &mut acc_curve);
-            mem::swap(&mut players_active[i].curve, &mut acc_curve);
+        let mut curve_colors = [GameColor::default(); MAX_PLAYERS];
+        for k in 0..n {
+            curve_colors[k] = players[active[k]].curve.color;
         }
-
-        let n = players_active.len();
-        if n % 2 == 0 {
-            mem::swap(&mut players_active[n-1].curve, &mut acc_curve);
+        // every player gets the curve of the next one, the last one that of the first
+        for k in 0..n-1 {
+            let (left, right) = players.split_at_mut(active[k+1]);
+            mem::swap(&mut left[active[k]].curve, &mut right[0].curve);
         }
-        for (p, c) in players_active.iter_mut().zip(curve_colors) {
-            p.curve.color = c;
+        for k in 0..n {
+            players[active[k]].curve.color = curve_colors[k];
         }
     }
 
+    fn recolors_traces(&self) -> bool { true }
+
     fn draw(&self) -> ImgIterator {
         ImgIterator::new(&IMG_SWAP, 10, self.pos)
     }
@@ -484,3 +482,69 @@ impl Buff for SwapBuffSprite {
         self.pos
     }
 }
+
+/// A buff lying on the field. Dispatching over an enum instead of `Box<Buff>` lets the buffs of
+/// a game live in fixed storage.
+pub enum BuffSprite {
+    Fast(FastPlayerBuffSprite),
+    Clear(ClearBuffSprite),
+    ChangeDir(ChangeDirBuffSprite),
+    Slow(SlowBuffSprite),
+    Color(ColorBuffSprite),
+    Big(BigBuffSprite),
+    Small(SmallBuffSprite),
+    Border(BorderBuffSprite),
+    Drunken(DrunkenBuffSprite),
+    Swap(SwapBuffSprite),
+}
+
+macro_rules! dispatch {
+    ($sprite:expr, $b:ident => $call:expr) => {
+        match $sprite {
+            BuffSprite::Fast($b) => $call,
+            BuffSprite::Clear($b) => $call,
+            BuffSprite::ChangeDir($b) => $call,
+            BuffSprite::Slow($b) => $call,
+            BuffSprite::Color($b) => $call,
+            BuffSprite::Big($b) => $call,
+            BuffSprite::Small($b) => $call,
+            BuffSprite::Border($b) => $call,
+            BuffSprite::Drunken($b) => $call,
+            BuffSprite::Swap($b) => $call,
+        }
+    };
+}
+
+impl Buff for BuffSprite {
+    fn apply_players(&self, players: &mut [Player], collector_id: usize) {
+        dispatch!(self, b => b.apply_players(players, collector_id))
+    }
+
+    fn apply_player(&self, player: &mut Curve, collector: bool) {
+        dispatch!(self, b => b.apply_player(player, collector))
+    }
+
+    fn apply_border(&self, border: &mut Border) {
+        dispatch!(self, b => b.apply_border(border))
+    }
+
+    fn clear_screen(&self) -> bool {
+        dispatch!(self, b => b.clear_screen())
+    }
+
+    fn recolors_traces(&self) -> bool {
+        dispatch!(self, b => b.recolors_traces())
+    }
+
+    fn draw(&self) -> ImgIterator {
+        dispatch!(self, b => b.draw())
+    }
+
+    fn aabb(&self) -> (Coord, Coord) {
+        dispatch!(self, b => b.aabb())
+    }
+
+    fn get_pos(&self) -> Coord {
+        dispatch!(self, b => b.get_pos())
+    }
+}
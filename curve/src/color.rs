@@ -0,0 +1,123 @@
+use embedded_graphics::pixelcolor::PixelColor;
+
+/// A 24 bit RGB color, `0xRR_GGBB`.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct GameColor {
+    pub value: u32,
+}
+
+impl PixelColor for GameColor {}
+
+impl From<u8> for GameColor {
+    fn from(other: u8) -> Self {
+        GameColor {
+            value: u32::from(other),
+        }
+    }
+}
+
+impl GameColor {
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        GameColor {
+            value: u32::from(r) << 16 | u32::from(g) << 8 | u32::from(b),
+        }
+    }
+
+    pub fn r(self) -> u8 {
+        (self.value >> 16) as u8
+    }
+
+    pub fn g(self) -> u8 {
+        (self.value >> 8) as u8
+    }
+
+    pub fn b(self) -> u8 {
+        self.value as u8
+    }
+
+    /// Expands the 5/6/5 bit channels, white stays white.
+    pub fn from_rgb565(value: u16) -> Self {
+        let r = (value >> 11) as u8 & 0x1F;
+        let g = (value >> 5) as u8 & 0x3F;
+        let b = value as u8 & 0x1F;
+        Self::from_rgb(r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2)
+    }
+
+    pub fn to_rgb565(self) -> u16 {
+        u16::from(self.r() >> 3) << 11 | u16::from(self.g() >> 2) << 5 | u16::from(self.b() >> 3)
+    }
+
+    /// The color of a pixel in the LTDC layer format, the alpha is dropped.
+    pub fn from_argb8888(value: u32) -> Self {
+        GameColor {
+            value: value & 0xFF_FFFF,
+        }
+    }
+
+    pub fn to_argb8888(self, alpha: u8) -> u32 {
+        u32::from(alpha) << 24 | (self.value & 0xFF_FFFF)
+    }
+
+    /// `hue` in degrees, any value is taken modulo 360. `saturation` and `value` from 0 to 1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let mut h = libm::fmodf(hue, 360_f32);
+        if h < 0_f32 {
+            h += 360_f32;
+        }
+        let s = saturation.max(0_f32).min(1_f32);
+        let v = value.max(0_f32).min(1_f32);
+        let c = v * s;
+        let x = c * (1_f32 - (libm::fmodf(h / 60_f32, 2_f32) - 1_f32).abs());
+        let m = v - c;
+        let (r, g, b) = match (h / 60_f32) as u32 {
+            0 => (c, x, 0_f32),
+            1 => (x, c, 0_f32),
+            2 => (0_f32, c, x),
+            3 => (0_f32, x, c),
+            4 => (x, 0_f32, c),
+            _ => (c, 0_f32, x),
+        };
+        let channel = |f: f32| libm::roundf((f + m) * 255_f32) as u8;
+        Self::from_rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// Hue in degrees (0 for grays), saturation and value from 0 to 1.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let r = f32::from(self.r()) / 255_f32;
+        let g = f32::from(self.g()) / 255_f32;
+        let b = f32::from(self.b()) / 255_f32;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let hue = if d == 0_f32 {
+            0_f32
+        } else if max == r {
+            let h = 60_f32 * (g - b) / d;
+            if h < 0_f32 { h + 360_f32 } else { h }
+        } else if max == g {
+            60_f32 * ((b - r) / d + 2_f32)
+        } else {
+            60_f32 * ((r - g) / d + 4_f32)
+        };
+        let saturation = if max == 0_f32 { 0_f32 } else { d / max };
+        (hue, saturation, max)
+    }
+
+    /// `self` at `t` = 0, `other` at `t` = 1.
+    pub fn lerp(self, other: GameColor, t: f32) -> Self {
+        let t = t.max(0_f32).min(1_f32);
+        let mix = |a: u8, b: u8| {
+            libm::roundf(f32::from(a) + (f32::from(b) - f32::from(a)) * t) as u8
+        };
+        Self::from_rgb(mix(self.r(), other.r()), mix(self.g(), other.g()), mix(self.b(), other.b()))
+    }
+
+    /// `self` drawn with `alpha` (255 is opaque) over a pixel of color `below`.
+    pub fn blend_over(self, below: GameColor, alpha: u8) -> Self {
+        let a = u32::from(alpha);
+        let mix = |src: u8, dst: u8| {
+            ((u32::from(src) * a + u32::from(dst) * (255 - a) + 127) / 255) as u8
+        };
+        Self::from_rgb(mix(self.r(), below.r()), mix(self.g(), below.g()), mix(self.b(), below.b()))
+    }
+}
@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+use embedded_graphics::{
+    prelude::*,
+    coord::Coord,
+    fonts::Font6x8,
+    primitives::Rect,
+};
+
+use crate::color::GameColor;
+use crate::geometry::{AABBox, Vector2D};
+use crate::map::{Map, Obstacle};
+use crate::theme::Theme;
+
+/// Grid that new shapes snap to, in pixels.
+pub const GRID: i32 = 10;
+/// How close a tap has to be to an obstacle to erase it.
+pub const HIT_RADIUS: f32 = 6.0;
+pub const TOOLBAR_HEIGHT: i32 = 24;
+/// Half the size of a spawn zone placed with `Tool::Spawn`.
+const SPAWN_HALF_SIZE: i32 = 20;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Tool {
+    Line,
+    Rect,
+    Erase,
+    Spawn,
+}
+
+const TOOLS: [Tool; 4] = [Tool::Line, Tool::Rect, Tool::Erase, Tool::Spawn];
+const BUTTONS: [&str; 5] = ["Line", "Rect", "Erase", "Spawn", "Save"];
+
+/// Editing state of a map that is drawn with a finger.
+///
+/// The editor only knows about touch points and a `Drawing`, the caller polls the touch
+/// controller and passes the first touch (or `None`) to `touch` every frame.
+pub struct Editor {
+    pub map: Map,
+    pub tool: Tool,
+    width: i32,
+    height: i32,
+    drag: Option<(Coord, Coord)>,
+    pressed: bool,
+    to_erase: Vec<Obstacle>,
+    dirty: bool,
+}
+
+impl Editor {
+    pub fn new(map: Map, width: i32, height: i32) -> Self {
+        Self {
+            map,
+            tool: Tool::Line,
+            width,
+            height,
+            drag: None,
+            pressed: false,
+            to_erase: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    /// Area obstacles can be placed in, above the toolbar.
+    pub fn field(&self) -> AABBox {
+        AABBox::new(Coord::new(0, 0), Coord::new(self.width - 1, self.height - TOOLBAR_HEIGHT - 1))
+    }
+
+    /// Rounds the point to the closest grid point inside the field.
+    pub fn snap(&self, point: Coord) -> Coord {
+        let field = self.field();
+        let snap = |v: i32, max: i32| (((v + GRID / 2) / GRID) * GRID).max(0).min(max);
+        Coord::new(snap(point[0], field.bottom_right[0]), snap(point[1], field.bottom_right[1]))
+    }
+
+    /// Index of the toolbar button under the point.
+    pub fn button_at(&self, point: Coord) -> Option<usize> {
+        if point[1] < self.height - TOOLBAR_HEIGHT { return None; }
+        let w = self.width / BUTTONS.len() as i32;
+        Some(((point[0] / w) as usize).min(BUTTONS.len() - 1))
+    }
+
+    /// Index of the obstacle under the point.
+    pub fn obstacle_at(&self, point: Coord) -> Option<usize> {
+        let p = Vector2D{x: point[0] as f32, y: point[1] as f32};
+        self.map.obstacles.iter()
+                          .enumerate()
+                          .map(|(i, o)| (i, o.distance(p)))
+                          .filter(|(_, d)| *d <= HIT_RADIUS)
+                          .fold(None, |best: Option<(usize, f32)>, (i, d)| match best {
+                              Some((_, bd)) if bd <= d => best,
+                              _ => Some((i, d)),
+                          })
+                          .map(|(i, _)| i)
+    }
+
+    /// Index of the spawn zone under the point.
+    pub fn spawn_at(&self, point: Coord) -> Option<usize> {
+        self.map.spawns.iter().position(|s| s.inside(point))
+    }
+
+    /// Handles the current touch. Returns `true` when "Save" was tapped.
+    pub fn touch(&mut self, touch: Option<Coord>) -> bool {
+        let point = match touch {
+            Some(p) => p,
+            None => {
+                self.pressed = false;
+                if let Some((start, end)) = self.drag.take() {
+                    self.finish_drag(start, end);
+                }
+                return false;
+            },
+        };
+        if let Some((start, end)) = self.drag {
+            let end_new = self.snap(point);
+            if end_new != end {
+                self.to_erase.push(preview_shape(self.tool, start, end));
+                self.drag = Some((start, end_new));
+                self.dirty = true;
+            }
+            return false;
+        }
+        if self.pressed { return false; }
+        self.pressed = true;
+        if let Some(b) = self.button_at(point) {
+            if b == TOOLS.len() { return true; }
+            self.tool = TOOLS[b];
+            self.dirty = true;
+            return false;
+        }
+        match self.tool {
+            Tool::Line | Tool::Rect => {
+                let start = self.snap(point);
+                self.drag = Some((start, start));
+            },
+            Tool::Erase => self.erase_at(point),
+            Tool::Spawn => self.add_spawn(self.snap(point)),
+        }
+        false
+    }
+
+    pub fn draw<D: Drawing<GameColor>>(&mut self, display: &mut D, theme: &Theme) {
+        if !self.dirty { return; }
+        for o in self.to_erase.drain(..) {
+            o.draw(display, theme.background);
+        }
+        self.map.draw(display, theme.obstacle);
+        for s in &self.map.spawns {
+            display.draw(Rect::new(s.top_left, s.bottom_right)
+                            .with_stroke(Some(theme.accent))
+                            .into_iter());
+        }
+        if let Some((start, end)) = self.drag {
+            preview_shape(self.tool, start, end).draw(display, theme.text);
+        }
+        self.draw_toolbar(display, theme);
+        self.dirty = false;
+    }
+
+    fn draw_toolbar<D: Drawing<GameColor>>(&self, display: &mut D, theme: &Theme) {
+        let w = self.width / BUTTONS.len() as i32;
+        let top = self.height - TOOLBAR_HEIGHT;
+        for (i, name) in BUTTONS.iter().enumerate() {
+            let selected = i < TOOLS.len() && TOOLS[i] == self.tool;
+            let fill = if selected { theme.accent } else { theme.background };
+            let x = w * i as i32;
+            display.draw(Rect::new(Coord::new(x, top), Coord::new(x + w - 1, self.height - 1))
+                            .with_stroke(Some(theme.text))
+                            .with_fill(Some(fill))
+                            .into_iter());
+            let len = (name.len() * 6) as i32;
+            display.draw(Font6x8::render_str(name)
+                            .with_stroke(Some(theme.text))
+                            .with_fill(Some(fill))
+                            .translate(Coord::new(x + (w - len) / 2, top + (TOOLBAR_HEIGHT - 8) / 2))
+                            .into_iter());
+        }
+    }
+
+    fn finish_drag(&mut self, start: Coord, end: Coord) {
+        self.to_erase.push(preview_shape(self.tool, start, end));
+        self.dirty = true;
+        if start == end { return; }
+        match self.tool {
+            Tool::Line => self.map.obstacles.push(preview_shape(Tool::Line, start, end)),
+            Tool::Rect if start[0] != end[0] && start[1] != end[1] => {
+                self.map.obstacles.push(preview_shape(Tool::Rect, start, end));
+            },
+            _ => {},
+        }
+    }
+
+    fn erase_at(&mut self, point: Coord) {
+        if let Some(i) = self.obstacle_at(point) {
+            let o = self.map.obstacles.remove(i);
+            self.to_erase.push(o);
+            self.dirty = true;
+        } else if let Some(i) = self.spawn_at(point) {
+            let s = self.map.spawns.remove(i);
+            self.to_erase.push(Obstacle::Rect(s));
+            self.dirty = true;
+        }
+    }
+
+    fn add_spawn(&mut self, center: Coord) {
+        let field = self.field();
+        let top_left = Coord::new((center[0] - SPAWN_HALF_SIZE).max(0),
+                                  (center[1] - SPAWN_HALF_SIZE).max(0));
+        let bottom_right = Coord::new((center[0] + SPAWN_HALF_SIZE).min(field.bottom_right[0]),
+                                      (center[1] + SPAWN_HALF_SIZE).min(field.bottom_right[1]));
+        self.map.spawns.push(AABBox::new(top_left, bottom_right));
+        self.dirty = true;
+    }
+}
+
+/// The obstacle a drag from `start` to `end` would create with the tool.
+fn preview_shape(tool: Tool, start: Coord, end: Coord) -> Obstacle {
+    match tool {
+        Tool::Rect if start[0] != end[0] && start[1] != end[1] => {
+            Obstacle::Rect(AABBox::new(Coord::new(start[0].min(end[0]), start[1].min(end[1])),
+                                       Coord::new(start[0].max(end[0]), start[1].max(end[1]))))
+        },
+        _ => Obstacle::Polyline(vec![
+            Vector2D{x: start[0] as f32, y: start[1] as f32},
+            Vector2D{x: end[0] as f32, y: end[1] as f32},
+        ]),
+    }
+}
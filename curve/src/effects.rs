@@ -0,0 +1,126 @@
+//! Visual feedback for crashes: a ring that grows and fades out at the crash point, and a cross
+//! that marks where a curve died for the rest of the round. Effects only draw, they never take
+//! part in collisions. They stay inside the field, the HUD in the padding is drawn only once
+//! per round and would not come back after a repaint.
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line};
+use heapless::Vec;
+use heapless::consts::U4;
+
+use crate::color::GameColor;
+use crate::geometry::{AABBox, Vector2D};
+use crate::player::field_box;
+
+/// Simulation ticks a crash ring is shown.
+pub const RING_TICKS: usize = 40;
+const RING_START: f32 = 3_f32;
+const RING_END: f32 = 18_f32;
+/// Half the size of the death marker.
+const MARKER_SIZE: i32 = 3;
+
+pub struct Ring {
+    pos: Vector2D,
+    color: GameColor,
+    age: usize,
+    /// Area of the last drawn frame, repainted before the next one.
+    drawn: Option<AABBox>,
+}
+
+impl Ring {
+    pub fn radius(&self) -> f32 {
+        let t = (self.age as f32 / RING_TICKS as f32).min(1_f32);
+        RING_START + (RING_END - RING_START) * t
+    }
+
+    pub fn done(&self) -> bool {
+        self.age >= RING_TICKS
+    }
+
+    /// Takes the area of the last frame, it has to be repainted.
+    pub fn take_drawn(&mut self) -> Option<AABBox> {
+        self.drawn.take()
+    }
+
+    /// Draws the ring, fading from the player color into `background`.
+    pub fn draw<D: Drawing<GameColor>>(&mut self, display: &mut D, background: GameColor) {
+        let fade = self.age as f32 / RING_TICKS as f32;
+        let color = self.color.lerp(background, fade);
+        let r = self.radius();
+        let center = Coord::new(self.pos.x as i32, self.pos.y as i32);
+        let field = field_box();
+        display.draw(Circle::new(center, r as u32)
+                        .with_stroke(Some(color))
+                        .into_iter()
+                        .filter(|p| field.inside(Coord::new(p.0[0] as i32, p.0[1] as i32))));
+        let reach = r as i32 + 1;
+        self.drawn = AABBox::new(Coord::new(center[0] - reach, center[1] - reach),
+                                 Coord::new(center[0] + reach, center[1] + reach))
+                         .intersection(&field);
+    }
+}
+
+/// Cross at the spot a curve died.
+pub struct Marker {
+    pos: Vector2D,
+    color: GameColor,
+}
+
+impl Marker {
+    pub fn draw<D: Drawing<GameColor>>(&self, display: &mut D) {
+        let (x, y, s) = (self.pos.x as i32, self.pos.y as i32, MARKER_SIZE);
+        let field = field_box();
+        let inside = |p: &Pixel<GameColor>| field.inside(Coord::new(p.0[0] as i32, p.0[1] as i32));
+        display.draw(Line::new(Coord::new(x - s, y - s), Coord::new(x + s, y + s))
+                        .with_stroke(Some(self.color))
+                        .into_iter()
+                        .filter(inside));
+        display.draw(Line::new(Coord::new(x - s, y + s), Coord::new(x + s, y - s))
+                        .with_stroke(Some(self.color))
+                        .into_iter()
+                        .filter(inside));
+    }
+}
+
+/// The effects of one round, there is at most one crash per player.
+pub struct Effects {
+    pub rings: Vec<Ring, U4>,
+    pub markers: Vec<Marker, U4>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            rings: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rings.clear();
+        self.markers.clear();
+    }
+
+    pub fn crash(&mut self, pos: Vector2D, color: GameColor) {
+        self.rings.push(Ring{pos, color, age: 0, drawn: None}).ok();
+        self.markers.push(Marker{pos, color}).ok();
+    }
+
+    /// Ages the rings by `dt` simulation ticks.
+    pub fn advance(&mut self, dt: usize) {
+        for r in self.rings.iter_mut() {
+            r.age += dt;
+        }
+    }
+
+    /// Drops the rings that are over, their last frame has to be repainted before.
+    pub fn remove_done(&mut self) {
+        let mut i = 0;
+        while i < self.rings.len() {
+            if self.rings[i].done() && self.rings[i].drawn.is_none() {
+                self.rings.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,843 @@
+use core::cmp::Reverse;
+use core::fmt::Write;
+use core::mem;
+use heapless::{String, Vec};
+use heapless::consts::{U4, U8, U16, U32, U64};
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Line, Rect},
+};
+
+use crate::{
+    HEIGHT, WIDTH,
+    color::GameColor,
+    player::{PAD_LEFT, PAD_RIGHT, PAD_BOTTOM, PAD_TOP,
+             FIELD_LEFT, FIELD_RIGHT, FIELD_TOP, FIELD_BOTTOM},
+    buffs::{
+        Buff, BuffSprite, BigBuffSprite, SmallBuffSprite, FastPlayerBuffSprite, SlowBuffSprite, ChangeDirBuffSprite,
+        ClearBuffSprite, ColorBuffSprite, BorderBuffSprite, DrunkenBuffSprite, SwapBuffSprite,
+    },
+    random::Random,
+    geometry::{AABBox, Vector2D},
+    border::{Border, Shrink},
+    player::{Curve, Collide, CollideSelf, Handicap, PlayerInput, field_box},
+    settings::{Settings, MAX_PLAYERS},
+    scoring::{Scoring, ScoringRule},
+    map::{Map, EdgeBehavior},
+    spawn::{Spawn, plan_spawns},
+    theme::{Palette, Theme, theme},
+    effects::{Effects, RING_TICKS},
+    trace::{TraceBuffer, TracePool},
+};
+
+// free space around a buff
+const BUFF_CLEARANCE: f32 = 10.0;
+// half the diagonal of a buff, anything closer to its center may touch it
+const BUFF_REACH: f32 = 7.5;
+// pixels between two buffs
+const BUFF_GAP: i32 = 4;
+// length of the path in front of a curve that is kept free of new buffs
+const BUFF_AHEAD: f32 = 40.0;
+// ticks until the next try if no free spot was found
+const BUFF_RETRY: isize = 20;
+/// The last crash of a round plays this many times slower.
+const SLOW_MOTION_FACTOR: usize = 4;
+const PLACEMENT_TRIES: usize = 20;
+/// No new buffs show up while this many are lying on the field.
+type MaxBuffs = U16;
+/// The players that crashed in one step with the cause: a suicide and a hit or two head-ons
+/// for each pair of players, the border and a wall for each player at most.
+type Losers = Vec<(usize, DeathCause), U32>;
+/// Most a `Game` may take up on the stack of the firmware, in bytes. The traces are kept in
+/// a `TracePool` outside of it.
+pub const GAME_STACK_BUDGET: usize = 8 * 1024;
+// fails to compile if a game doesn't fit into its stack budget
+#[allow(dead_code)]
+const GAME_FITS_STACK: [(); 0] = [(); (mem::size_of::<Game<'static>>() > GAME_STACK_BUDGET)
+                                       as usize];
+
+/// Room for a name of `keyboard::NAME_LEN` characters.
+pub type Name = String<U8>;
+/// Room for the longest message of a round, like the winner.
+pub type Message = String<U32>;
+
+pub struct InputRegion {
+    sensitive_rect: AABBox
+}
+
+impl InputRegion {
+    pub fn new(boxx: AABBox) -> Self {
+        Self {
+            sensitive_rect: boxx
+        }
+    }
+
+    pub fn is_active(&self, touches: &[Coord]) -> bool {
+        for touch in touches {
+            if self.sensitive_rect.inside(touch.clone()) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Why a player has lost the round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeathCause {
+    /// Ran into the trace of the given player.
+    CutOffBy(usize),
+    /// Ran into its own trace.
+    Suicide,
+    Border,
+    /// Crashed head first into the given player.
+    HeadOn(usize),
+    /// Ran into an obstacle of the map.
+    Wall,
+}
+
+/// Statistics of a player over the current match.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerStats {
+    pub kills: u32,
+    pub deaths: u32,
+    pub suicides: u32,
+    pub border_deaths: u32,
+    pub wall_deaths: u32,
+    pub head_ons: u32,
+}
+
+impl PlayerStats {
+    /// The summary line, like "kills 2, deaths 3, suicides 1".
+    pub fn describe(&self) -> String<U64> {
+        let mut text = String::new();
+        write!(text, "kills {}, deaths {}, suicides {}", self.kills, self.deaths, self.suicides)
+            .ok();
+        text
+    }
+}
+
+pub struct Player<'a> {
+    /// Shown in the HUD and messages, "Player A" to "Player D" unless a profile is used.
+    pub name: Name,
+    pub score: i32,
+    /// Points gained in the current round.
+    pub round_score: i32,
+    /// Time alive in the current round, in ticks.
+    pub alive_ticks: u32,
+    pub lost: bool,
+    pub team: Option<usize>,
+    pub death: Option<DeathCause>,
+    pub stats: PlayerStats,
+    /// Takes effect from the next round on.
+    pub handicap: Handicap,
+    pub curve: Curve<'a>,
+    color: GameColor,
+    input_left: InputRegion,
+    input_right: InputRegion,
+}
+
+impl<'a> Player<'a> {
+    /// A player whose curve leaves its trace in `trace`.
+    pub fn new(color: GameColor, radius: u32, rng: &mut Random, input_left: AABBox,
+               input_right: AABBox, trace: &'a mut TraceBuffer) -> Self {
+        Self {
+            name: Name::new(),
+            score: 0,
+            round_score: 0,
+            alive_ticks: 0,
+            lost: false,
+            team: None,
+            death: None,
+            stats: PlayerStats::default(),
+            handicap: Handicap::default(),
+            color,
+            curve: Curve::new(color, rand_pos(rng), radius,
+                              (rng.next_u32() % 360) as f32, trace),
+            input_left: InputRegion::new(input_left),
+            input_right: InputRegion::new(input_right),
+        }
+    }
+
+    pub fn clear_trace(&mut self) {
+        self.curve.clear_trace();
+    }
+
+    pub fn reset(&mut self, pos: (f32, f32), angle: f32) {
+        self.curve.restart(self.color, pos, angle, self.handicap);
+        self.lost = false;
+        self.death = None;
+        self.round_score = 0;
+        self.alive_ticks = 0;
+    }
+
+    pub fn add_points(&mut self, points: i32) {
+        self.score += points;
+        self.round_score += points;
+    }
+
+    /// How the player steers with the touches on the input regions.
+    pub fn touch_input(&self, touches: &[Coord]) -> PlayerInput {
+        match (self.input_left.is_active(touches),
+               self.input_right.is_active(touches)) {
+            (true, true) => PlayerInput::Both,
+            (false, false) => PlayerInput::None,
+            (true, false) => PlayerInput::Left,
+            (false, true) => PlayerInput::Right,
+        }
+    }
+
+    pub fn act(&mut self, input: PlayerInput) {
+        if self.lost { return; }
+        self.curve.act(input);
+    }
+
+    pub fn draw<D: Drawing<GameColor>>(&self, display: &mut D, palette: &Palette) {
+        if cfg!(debug_assertions) {
+            display.draw(Rect::new(self.input_left.sensitive_rect.top_left,
+                                   self.input_left.sensitive_rect.bottom_right)
+                        .with_stroke(Some(self.color))
+                        .into_iter());
+            display.draw(Rect::new(self.input_right.sensitive_rect.top_left,
+                                   self.input_right.sensitive_rect.bottom_right)
+                        .with_stroke(Some(self.color))
+                        .into_iter());
+        }
+        self.curve.draw(display, palette);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GameState {
+    Playing,
+    Finished,
+}
+
+pub struct Game<'a> {
+    pub players: Vec<Player<'a>, U4>,
+    pub buffs_collected: u32,
+    pub scoring: Scoring,
+    settings: Settings,
+    finished: bool,
+    buffs: Vec<BuffSprite, MaxBuffs>,
+    tt_update: isize,
+    tt_new_buff: isize,
+    last_time_update: isize,
+    border: Border,
+    map: Map,
+    spawns: Vec<Spawn, U4>,
+    theme: Theme,
+    effects: Effects,
+    /// Ticks of slow motion left after the final crash.
+    slow_motion: isize,
+    slow_ticks: usize,
+}
+
+impl<'a> Game<'a> {
+
+    /// A game for a player per color, player `i` keeps its trace in buffer `i` of `traces`.
+    pub fn new(player_colors: &[GameColor], settings: Settings, scoring: Scoring, map: Map,
+               rng: &mut Random, traces: &'a mut TracePool) -> Self {
+        let buffs = Vec::new();
+        let mut players: Vec<Player<'a>, U4> = Vec::new();
+        for ((i, c), trace) in player_colors.iter().enumerate().zip(traces.buffers()) {
+            let (left, right) = match i % 4 {
+                0 => (
+                    AABBox::new(Coord::new((3*WIDTH/4) as i32, (HEIGHT/2) as i32),
+                                Coord::new(WIDTH as i32, HEIGHT as i32)),
+                    AABBox::new(Coord::new((3*WIDTH/4) as i32, 0),
+                                Coord::new(WIDTH as i32, (HEIGHT/2) as i32)),
+                ),
+                1 => (
+                    AABBox::new(Coord::new(0, 0),
+                                Coord::new((WIDTH/4) as i32, (HEIGHT/2) as i32)),
+                    AABBox::new(Coord::new(0, (HEIGHT/2) as i32),
+                                Coord::new((WIDTH/4) as i32, HEIGHT as i32)),
+                ),
+                2 => (
+                    AABBox::new(Coord::new((WIDTH/2) as i32, 0),
+                                Coord::new((3*WIDTH/4) as i32, (HEIGHT/2) as i32)),
+                    AABBox::new(Coord::new((WIDTH/4) as i32, 0),
+                                Coord::new((WIDTH/2) as i32, (HEIGHT/2) as i32)),
+                ),
+                3 => (
+                    AABBox::new(Coord::new((WIDTH/4) as i32, (HEIGHT/2) as i32),
+                                Coord::new((WIDTH/2) as i32, HEIGHT as i32)),
+                    AABBox::new(Coord::new((WIDTH/2) as i32, (HEIGHT/2) as i32),
+                                Coord::new((3*WIDTH/4) as i32, HEIGHT as i32)),
+                ),
+                _ => unreachable!(),
+            };
+            let mut player = Player::new(*c, settings.curve_radius, rng, left, right, trace);
+            write!(player.name, "Player {}", (b'A' + i as u8) as char).ok();
+            players.push(player).ok();
+        }
+        if settings.teams && players.len() == 4 {
+            for (i, p) in players.iter_mut().enumerate() {
+                p.team = Some(i / 2);
+            }
+        }
+        Self {
+            players,
+            buffs_collected: 0,
+            scoring,
+            settings,
+            finished: false,
+            buffs,
+            tt_update: 0,
+            last_time_update: 0,
+            tt_new_buff: 0,
+            border: if settings.shrinking_border {
+                Border::with_shrink(Shrink {
+                    grace: settings.shrink_grace,
+                    interval: settings.shrink_interval,
+                    step: settings.shrink_step,
+                })
+            } else {
+                Border::new()
+            },
+            map,
+            spawns: Vec::new(),
+            theme: *theme(settings.theme as usize),
+            effects: Effects::new(),
+            slow_motion: 0,
+            slow_ticks: 0,
+        }
+    }
+
+    pub fn new_game(&mut self, rng: &mut Random) {
+        self.tt_update = 0;
+        self.last_time_update = 0;
+        self.tt_new_buff = 0;
+        self.buffs_collected = 0;
+        self.finished = false;
+        self.buffs.clear();
+        self.effects.clear();
+        self.slow_motion = 0;
+        self.slow_ticks = 0;
+        self.border.reset();
+        self.spawns = plan_spawns(rng, &self.map, &self.border, self.players.len(),
+                                  self.settings.symmetric_spawns);
+        for (p, s) in self.players.iter_mut().zip(self.spawns.iter()) {
+            p.reset((s.pos.x, s.pos.y), s.angle);
+            p.curve.set_edges(self.map.edges);
+        }
+    }
+
+    /// Draws the parts of the arena that never change during a round.
+    pub fn draw_arena<D: Drawing<GameColor>>(&self, display: &mut D) {
+        self.map.draw(display, self.theme.obstacle);
+        self.draw_edges(display);
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn border(&self) -> &Border {
+        &self.border
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Colors of the curves, the team colors in team games.
+    pub fn palette(&self) -> &Palette {
+        self.theme.player_colors(self.settings.teams && self.players.len() == 4)
+    }
+
+    /// Where the curves started the current round.
+    pub fn spawns(&self) -> &[Spawn] {
+        &self.spawns
+    }
+
+    fn draw_edges<D: Drawing<GameColor>>(&self, display: &mut D) {
+        let (l, r) = (FIELD_LEFT as i32, FIELD_RIGHT as i32);
+        let (t, b) = (FIELD_TOP as i32, FIELD_BOTTOM as i32);
+        let edges = [
+            (self.map.edges.left, Coord::new(l, t), Coord::new(l, b)),
+            (self.map.edges.right, Coord::new(r, t), Coord::new(r, b)),
+            (self.map.edges.top, Coord::new(l, t), Coord::new(r, t)),
+            (self.map.edges.bottom, Coord::new(l, b), Coord::new(r, b)),
+        ];
+        for (behavior, start, end) in edges.iter() {
+            let color = match behavior {
+                EdgeBehavior::Wrap => continue,
+                EdgeBehavior::Wall => self.theme.wall_edge,
+                EdgeBehavior::Bounce => self.theme.bounce_edge,
+            };
+            display.draw(Line::new(*start, *end)
+                            .with_stroke(Some(color))
+                            .into_iter());
+        }
+    }
+
+    fn update_buffs(&mut self, rng: &mut Random, dt: usize) {
+        if !self.settings.buffs_enabled { return; }
+        self.tt_new_buff -= dt as isize;
+
+        if self.tt_new_buff < 0 {
+            self.tt_new_buff = (rng.next_u32() % self.settings.max_tt_buff.max(1)) as isize;
+            if !self.place_buff(rng) {
+                // crowded field, try again soon
+                self.tt_new_buff = BUFF_RETRY;
+            }
+        }
+    }
+
+    /// Puts a random buff on a free spot. Returns `false` if none was found.
+    ///
+    /// A spot in front of a curve is only taken if every free spot that was tried is. On a
+    /// crowded field that is still better than no buff, and the curve has `BUFF_AHEAD` pixels
+    /// to steer around it.
+    pub fn place_buff(&mut self, rng: &mut Random) -> bool {
+        let mut spot = None;
+        for _ in 0..PLACEMENT_TRIES {
+            let buff = new_rand_buff(rng, &self.border);
+            let pos = buff.get_pos();
+            let center = Vector2D{x: (pos[0] + 5) as f32, y: (pos[1] + 5) as f32};
+            if self.buff_spot_taken(center) {
+                continue;
+            }
+            let in_the_way = self.ahead_of_curve(center);
+            if spot.is_none() || !in_the_way {
+                spot = Some(buff);
+            }
+            if !in_the_way {
+                break;
+            }
+        }
+        match spot {
+            Some(buff) => self.buffs.push(buff).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn buffs(&self) -> &[BuffSprite] {
+        &self.buffs
+    }
+
+    /// Whether a buff centered at `center` would lie on an obstacle, a trace or another buff.
+    pub fn buff_spot_taken(&self, center: Vector2D) -> bool {
+        if self.map.distance(center) <= BUFF_CLEARANCE {
+            return true;
+        }
+        let overlaps_buff = self.buffs.iter().any(|b| {
+            let pos = b.get_pos();
+            (pos[0] + 5 - center.x as i32).abs() < 10 + BUFF_GAP
+                && (pos[1] + 5 - center.y as i32).abs() < 10 + BUFF_GAP
+        });
+        if overlaps_buff {
+            return true;
+        }
+        self.players.iter().any(|p| {
+            p.curve.trace().iter().any(|seg| {
+                center.distance_to_segment(seg.start, seg.end) < seg.radius + BUFF_REACH
+            })
+        })
+    }
+
+    /// Whether a buff centered at `center` would be right in front of a living curve.
+    pub fn ahead_of_curve(&self, center: Vector2D) -> bool {
+        self.players.iter().filter(|p| !p.lost).any(|p| {
+            let head = p.curve.pos();
+            let ahead = head + p.curve.direction() * BUFF_AHEAD;
+            center.distance_to_segment(head, ahead) < p.curve.effective_radius() + BUFF_REACH
+        })
+    }
+
+    /// Inputs of all players from the touches on their input regions.
+    pub fn touch_inputs(&self, touches: &[Coord]) -> Vec<PlayerInput, U4> {
+        self.players.iter().map(|p| p.touch_input(touches)).collect()
+    }
+
+    fn act(&mut self, inputs: &[PlayerInput], _dt:usize) {
+        for (i, p) in self.players.iter_mut().enumerate() {
+            p.act(inputs.get(i).cloned().unwrap_or(PlayerInput::None));
+        }
+    }
+
+    fn player_player_collision(&self, losers: &mut Losers) {
+        for i in 0..self.players.len() {
+            let (pis, pjs) = self.players.split_at(i+1);
+            let pi = pis.last().unwrap();
+
+            if pi.curve.collides() {
+                if cfg!(debug_assertions) {println!("self collision {}", i);}
+                losers.push((i, DeathCause::Suicide)).ok();
+            }
+            for (h, pj) in pjs.iter().enumerate() {
+                let j = h+i+1;
+                if self.settings.friendly_trails_passable && pi.team.is_some()
+                   && pi.team == pj.team {
+                    continue;
+                }
+                if !pi.lost && !pj.lost && pi.curve.head_on(&pj.curve) {
+                    if cfg!(debug_assertions) {println!("head on {} {}", i, j);}
+                    losers.push((i, DeathCause::HeadOn(j))).ok();
+                    losers.push((j, DeathCause::HeadOn(i))).ok();
+                    continue;
+                }
+                if pi.curve.collides_with(&pj.curve) {
+                    if cfg!(debug_assertions) {println!("collision i {}", i);}
+                    losers.push((i, DeathCause::CutOffBy(j))).ok();
+                }
+                if pj.curve.collides_with(&pi.curve) {
+                    if cfg!(debug_assertions) {println!("collision j {}", j);}
+                    losers.push((j, DeathCause::CutOffBy(i))).ok();
+                }
+            }
+        }
+    }
+
+    fn player_border_collision(&self, losers: &mut Losers) {
+        for (i, p) in self.players.iter().enumerate() {
+            if (self.border.active && p.curve.collides_with(&self.border))
+               || p.curve.collides_with(&self.map.edges) {
+                losers.push((i, DeathCause::Border)).ok();
+            }
+        }
+    }
+
+    fn player_obstacle_collision(&self, losers: &mut Losers) {
+        for (i, p) in self.players.iter().enumerate() {
+            if self.map.obstacles.iter().any(|o| p.curve.collides_with(o)) {
+                losers.push((i, DeathCause::Wall)).ok();
+            }
+        }
+    }
+
+    fn player_buff_collision<D>(&mut self, display: &mut D)
+    where D: Drawing<GameColor> {
+        let mut repaint_all = false;
+        let mut collected_buffs: Vec<(usize, usize), MaxBuffs> = Vec::new();
+        for (i, b) in self.buffs.iter_mut().enumerate() {
+
+            for (j, p) in self.players.iter().enumerate() {
+                if p.curve.collides_with(b) {
+                    collected_buffs.push((i, j)).ok();
+                    b.apply_border(&mut self.border);
+
+                    repaint_all |= b.clear_screen() || b.recolors_traces();
+                    break;
+                }
+            }
+        }
+        collected_buffs.reverse();
+        let mut erased: Vec<AABBox, MaxBuffs> = Vec::new();
+        for &(b_i, collecter_id) in collected_buffs.iter() {
+            self.buffs[b_i].apply_players(&mut self.players, collecter_id);
+            let aabb = self.buffs.swap_remove(b_i).aabb();
+            erased.push(AABBox::new(aabb.0, aabb.1)).ok();
+            self.buffs_collected += 1;
+        }
+        if repaint_all {
+            self.repaint_field(display);
+        } else {
+            for region in erased.iter() {
+                self.repaint(display, region);
+            }
+        }
+    }
+
+    /// Repaints the whole field from the game state.
+    pub fn repaint_field<D: Drawing<GameColor>>(&mut self, display: &mut D) {
+        self.repaint(display, &field_box());
+    }
+
+    /// Restores what the game shows inside `region`, e.g. after a buff icon or a menu was
+    /// drawn over it.
+    pub fn repaint<D: Drawing<GameColor>>(&mut self, display: &mut D, region: &AABBox) {
+        display.draw(Rect::new(region.top_left, region.bottom_right)
+                        .with_fill(Some(self.theme.background))
+                        .into_iter());
+        for p in self.players.iter() {
+            p.curve.draw_trace(display, region);
+        }
+        self.draw_arena(display);
+        if self.border.active {
+            self.border.drawn = false;
+            self.border.draw(display, &self.theme);
+        }
+        for b in self.buffs.iter() {
+            let aabb = b.aabb();
+            if region.intersection(&AABBox::new(aabb.0, aabb.1)).is_some() {
+                display.draw(b.draw());
+            }
+        }
+    }
+
+    fn remove_buffs_outside<D>(&mut self, display: &mut D)
+    where D: Drawing<GameColor> {
+        let bounds = self.border.bounds();
+        let mut i = 0;
+        while i < self.buffs.len() {
+            let aabb = self.buffs[i].aabb();
+            if bounds.inside(aabb.0) && bounds.inside(aabb.1) {
+                i += 1;
+            } else {
+                self.buffs.swap_remove(i);
+                self.repaint(display, &AABBox::new(aabb.0, aabb.1));
+            }
+        }
+    }
+
+    /// Ends the round for the `losers` of a step, the first cause found for a player counts.
+    /// They are all marked as lost before the scoring sees them, so players dying together
+    /// share the higher place.
+    fn players_lost(&mut self, losers: &Losers) {
+        let mut new_losers: Vec<(usize, DeathCause), U4> = Vec::new();
+        for &(loser_i, cause) in losers.iter() {
+            let loser = &mut self.players[loser_i];
+            if loser.lost { continue; }
+            loser.lost = true;
+            loser.death = Some(cause);
+            new_losers.push((loser_i, cause)).ok();
+        }
+        for &(loser_i, cause) in new_losers.iter() {
+            self.player_lost(loser_i, cause);
+        }
+    }
+
+    fn player_lost(&mut self, loser_i: usize, cause: DeathCause) {
+        {
+            let loser = &mut self.players[loser_i];
+            loser.stats.deaths += 1;
+            match cause {
+                DeathCause::Suicide => loser.stats.suicides += 1,
+                DeathCause::Border => loser.stats.border_deaths += 1,
+                DeathCause::HeadOn(_) => loser.stats.head_ons += 1,
+                DeathCause::Wall => loser.stats.wall_deaths += 1,
+                DeathCause::CutOffBy(_) => {},
+            }
+        }
+        if let DeathCause::CutOffBy(killer) = cause {
+            if killer != loser_i {
+                self.players[killer].stats.kills += 1;
+            }
+        }
+        self.scoring.player_lost(&mut self.players, loser_i, cause);
+        let curve = &self.players[loser_i].curve;
+        self.effects.crash(curve.pos(), curve.color);
+        if self.settings.slow_motion && self.round_over() {
+            self.slow_motion = (RING_TICKS * SLOW_MOTION_FACTOR) as isize;
+        }
+    }
+
+    /// Repaints what the crash rings covered in the last frame and draws their next frame.
+    fn draw_effects<D: Drawing<GameColor>>(&mut self, display: &mut D) {
+        let mut regions: Vec<AABBox, U4> = Vec::new();
+        for r in self.effects.rings.iter_mut() {
+            if let Some(region) = r.take_drawn() {
+                regions.push(region).ok();
+            }
+        }
+        for region in regions.iter() {
+            self.repaint(display, region);
+        }
+        self.effects.remove_done();
+        for m in self.effects.markers.iter() {
+            m.draw(display);
+        }
+        let background = self.theme.background;
+        for r in self.effects.rings.iter_mut() {
+            r.draw(display, background);
+        }
+    }
+
+    pub fn team_score(&self, team: usize) -> i32 {
+        self.players.iter()
+                    .filter(|p| p.team == Some(team))
+                    .map(|p| p.score)
+                    .sum()
+    }
+
+    /// The team with players left, if a team game is over.
+    pub fn winning_team(&self) -> Option<usize> {
+        if self.arena_full() { return None; }
+        self.players.iter().find(|p| !p.lost).and_then(|p| p.team)
+    }
+
+    /// Announces the winner of the round, a team in team games.
+    pub fn winner_message(&self) -> Message {
+        let mut message = Message::new();
+        if self.arena_full() {
+            message.push_str("Arena full, nobody has won!").ok();
+        } else if let Some(team) = self.winning_team() {
+            write!(message, "{} has won!", team_name(team)).ok();
+        } else if let Some(p) = self.players.iter().find(|p| !p.lost) {
+            write!(message, "{} has won!", p.name).ok();
+        } else {
+            message.push_str("Nobody has won!").ok();
+        }
+        message
+    }
+
+    pub fn is_winner(&self, player: usize) -> bool {
+        if self.arena_full() { return false; }
+        match self.players[player].team {
+            Some(team) => self.winning_team() == Some(team),
+            None => !self.players[player].lost,
+        }
+    }
+
+    /// Players from first to last place with their place. Winners come first, the others are
+    /// ranked by how long they stayed alive. Players that died in the same tick share a place.
+    pub fn placements(&self) -> Vec<(usize, usize), U4> {
+        let key = |i: usize| {
+            let p = &self.players[i];
+            (!self.is_winner(i), p.lost, Reverse(p.alive_ticks))
+        };
+        let mut order: Vec<usize, U4> = (0..self.players.len()).collect();
+        order.sort_unstable_by_key(|i| (key(*i), *i));
+        let mut placements: Vec<(usize, usize), U4> = Vec::new();
+        for (n, i) in order.iter().enumerate() {
+            let place = if n > 0 && key(order[n - 1]) == key(*i) {
+                placements[n - 1].1
+            } else {
+                n + 1
+            };
+            placements.push((*i, place)).ok();
+        }
+        placements
+    }
+
+    /// Whether a trace ran out of memory, that ends the round without a winner.
+    pub fn arena_full(&self) -> bool {
+        self.players.iter().any(|p| p.curve.trace_full())
+    }
+
+    fn round_over(&self) -> bool {
+        if self.arena_full() { return true; }
+        let mut alive = self.players.iter().filter(|p| !p.lost);
+        match alive.next() {
+            None => true,
+            Some(first) if first.team.is_some() => alive.all(|p| p.team == first.team),
+            Some(_) => alive.next().is_none(),
+        }
+    }
+
+    /// Advances the game by `dt` ticks, `inputs` holds the steering of every player.
+    pub fn step<D>(&mut self, rng: &mut Random, display: &mut D, inputs: &[PlayerInput], dt: usize)
+                   -> GameState
+    where D: Drawing<GameColor> {
+        if self.round_over() {
+            if self.slow_motion > 0 {
+                // the curves stand still, only the last crash plays on slowly
+                self.slow_motion -= dt as isize;
+                self.slow_ticks += dt;
+                self.effects.advance(self.slow_ticks / SLOW_MOTION_FACTOR);
+                self.slow_ticks %= SLOW_MOTION_FACTOR;
+                self.draw_effects(display);
+                return GameState::Playing;
+            }
+            if !self.finished {
+                self.finished = true;
+                self.scoring.round_finished(&mut self.players);
+            }
+            return GameState::Finished;
+        }
+
+        for p in self.players.iter_mut().filter(|p| !p.lost) {
+            p.alive_ticks += dt as u32;
+        }
+
+        self.update_buffs(rng, dt);
+        self.effects.advance(dt);
+        if self.border.update(dt) {
+            self.remove_buffs_outside(display);
+        }
+        
+        self.tt_update -= dt as isize;
+        if self.tt_update < 0 {
+            self.tt_update = 3;
+
+            self.act(inputs, dt);
+
+            self.player_buff_collision(display);
+            let mut losers = Losers::new();
+            self.player_player_collision(&mut losers);
+            self.player_border_collision(&mut losers);
+            self.player_obstacle_collision(&mut losers);
+            self.players_lost(&losers);
+
+            for p in self.buffs.iter() {
+                display.draw(p.draw());
+            }
+            let palette = self.palette();
+            for p in &mut self.players.iter().filter(|p| !p.lost) {
+                p.draw(display, palette);
+            }
+            self.draw_effects(display);
+            // the outline of an inactive border is drawn black over the edges
+            let redraw_edges = !self.border.drawn;
+            self.border.draw(display, &self.theme);
+            if redraw_edges {
+                self.draw_edges(display);
+            }
+        }
+        GameState::Playing
+    }
+}
+
+/// Teams are formed by the players A and B against C and D.
+pub fn team_name(team: usize) -> &'static str {
+    match team {
+        0 => "Team AB",
+        _ => "Team CD",
+    }
+}
+
+/// `name` as the name of a player, cut off if it doesn't fit.
+pub fn player_name(name: &str) -> Name {
+    let mut short = Name::new();
+    for c in name.chars() {
+        if short.push(c).is_err() { break; }
+    }
+    short
+}
+
+fn rand_pos(rng: &mut Random) -> (f32, f32) {
+    (
+        PAD_LEFT + rng.next_u32() as f32 % (WIDTH as f32 - PAD_LEFT - PAD_RIGHT),
+        PAD_TOP + rng.next_u32() as f32 % (HEIGHT as f32 - PAD_TOP - PAD_BOTTOM),
+    )
+}
+
+/// Random position with at least `margin` pixels to every side of `bounds`.
+pub(crate) fn rand_pos_in(rng: &mut Random, bounds: &AABBox, margin: i32) -> (f32, f32) {
+    let w = (bounds.bottom_right[0] - bounds.top_left[0] - 2 * margin).max(1) as u32;
+    let h = (bounds.bottom_right[1] - bounds.top_left[1] - 2 * margin).max(1) as u32;
+    (
+        (bounds.top_left[0] + margin) as f32 + (rng.next_u32() % w) as f32,
+        (bounds.top_left[1] + margin) as f32 + (rng.next_u32() % h) as f32,
+    )
+}
+
+fn new_rand_buff(rng: &mut Random, border: &Border) -> BuffSprite {
+    // buffs are 10x10 pixels, keep them clear of the (possibly inactive) border
+    let pos_buff = if border.active {
+        let b = border.bounds();
+        let area = AABBox::new(b.top_left, Coord::new(b.bottom_right[0] - 10,
+                                                      b.bottom_right[1] - 10));
+        rand_pos_in(rng, &area, 2)
+    } else {
+        rand_pos(rng)
+    };
+    let pos_coord = Coord::new(pos_buff.0 as i32, pos_buff.1 as i32);
+    let rand = rng.next_u32();
+    match rand % 10 {
+        0 => BuffSprite::Fast(FastPlayerBuffSprite::new(pos_coord)),
+        1 => BuffSprite::Clear(ClearBuffSprite::new(pos_coord)),
+        2 => BuffSprite::ChangeDir(ChangeDirBuffSprite::new(pos_coord)),
+        3 => BuffSprite::Slow(SlowBuffSprite::new(pos_coord)),
+        4 => BuffSprite::Color(ColorBuffSprite::new(pos_coord)),
+        5 => BuffSprite::Big(BigBuffSprite::new(pos_coord)),
+        6 => BuffSprite::Small(SmallBuffSprite::new(pos_coord)),
+        7 => BuffSprite::Border(BorderBuffSprite::new(pos_coord)),
+        8 => BuffSprite::Drunken(DrunkenBuffSprite::new(pos_coord)),
+        9 => BuffSprite::Swap(SwapBuffSprite::new(pos_coord)),
+        _ => BuffSprite::Slow(SlowBuffSprite::new(pos_coord)),
+    }
+}
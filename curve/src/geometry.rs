@@ -0,0 +1,341 @@
+use embedded_graphics::prelude::Coord;
+use crate::color::GameColor;
+use embedded_graphics::prelude::Pixel;
+use embedded_graphics::prelude::UnsignedCoord;
+use core::ops::{Add, Sub, Mul};
+use libm::{cosf, sinf};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vector2D {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector2D {
+    pub fn rotate(self, angle: f32) -> Vector2D {
+        let ca = cosf(angle);
+        let sa = sinf(angle);
+        Vector2D {
+            x: ca * self.x - sa * self.y,
+            y: sa * self.x + ca * self.y,
+        }
+    }
+
+    pub fn length(self) -> f32 {
+        libm::sqrtf(self.x*self.x + self.y*self.y)
+    }
+
+    pub fn dot(self, other: Vector2D) -> f32 {
+        self.x*other.x + self.y*other.y
+    }
+
+    /// z component of the 3D cross product.
+    pub fn cross(self, other: Vector2D) -> f32 {
+        self.x*other.y - self.y*other.x
+    }
+
+    pub fn normalized(self) -> Vector2D {
+        let l = self.length();
+        Vector2D {x: self.x/l, y:self.y/l}
+    }
+
+    pub fn distance(self, other: Vector2D) -> f32 {
+        let vec = self - other;
+        libm::sqrtf(vec.dot(vec))
+    }
+
+    /// Distance to the line segment from `start` to `end`.
+    pub fn distance_to_segment(self, start: Vector2D, end: Vector2D) -> f32 {
+        let e = end - start;
+        let len2 = e.dot(e);
+        if len2 == 0_f32 {
+            return self.distance(start);
+        }
+        let t = ((self - start).dot(e) / len2).max(0_f32).min(1_f32);
+        self.distance(start + e * t)
+    }
+}
+
+/// Distance between the line segments `a`-`b` and `c`-`d`, 0 if they cross.
+pub fn segment_distance(a: Vector2D, b: Vector2D, c: Vector2D, d: Vector2D) -> f32 {
+    let d1 = (d - c).cross(a - c);
+    let d2 = (d - c).cross(b - c);
+    let d3 = (b - a).cross(c - a);
+    let d4 = (b - a).cross(d - a);
+    if d1 * d2 < 0_f32 && d3 * d4 < 0_f32 {
+        return 0_f32;
+    }
+    // touching and collinear segments end up here with a distance of 0
+    a.distance_to_segment(c, d)
+     .min(b.distance_to_segment(c, d))
+     .min(c.distance_to_segment(a, b))
+     .min(d.distance_to_segment(a, b))
+}
+
+impl Add<Point> for Vector2D {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        let new_x = self.x + (other.x as f32);
+        let new_y = self.y + (other.y as f32);
+        assert!(new_x >= 0.0, "New X value is less than 0!");
+        assert!(new_y >= 0.0, "New Y value is less than 0!");
+
+        Point {
+            x: (new_x as usize), y: (new_y as usize),
+        }
+    }
+}
+
+impl Add for Vector2D {
+    type Output = Vector2D;
+
+    fn add(self, other: Vector2D) -> Vector2D {
+        Vector2D {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl Sub for Vector2D {
+    type Output = Vector2D;
+
+    fn sub(self, other: Vector2D) -> Vector2D {
+        Vector2D {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl Mul<f32> for Vector2D {
+    type Output = Vector2D;
+
+    fn mul(self, scalar: f32) -> Vector2D {
+        Vector2D {x: self.x*scalar, y: self.y * scalar}
+    }
+}
+
+#[derive(Copy,Clone)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl Add<Vector2D> for Point {
+    type Output = Point;
+
+    fn add(self, other: Vector2D) -> Point {
+        let new_x = (self.x as f32) + other.x;
+        let new_y = (self.y as f32) + other.y;
+        assert!(new_x >= 0.0, "New X value is less than 0!");
+        assert!(new_y >= 0.0, "New Y value is less than 0!");
+
+        Point {
+            x: (new_x as usize), y: (new_y as usize),
+        }
+    }
+}
+
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AABBox {
+    pub top_left: Coord,
+    pub bottom_right: Coord
+}
+
+impl AABBox {
+    pub fn new(top_left: Coord, bottom_right: Coord) -> Self {
+        assert!(top_left[0] < bottom_right[0], "Left point is more to the right than right point");
+        assert!(top_left[1] < bottom_right[1], "Top point is more to the bottom than bottom point");
+        AABBox {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    pub fn inside(&self, point: Coord) -> bool {
+        (self.top_left[0] <= point[0] && self.top_left[1] <= point[1]
+        && point[0] <= self.bottom_right[0] && point[1] <= self.bottom_right[1])
+    }
+
+    pub fn contains(&self, point: Vector2D) -> bool {
+        self.top_left[0] as f32 <= point.x && self.top_left[1] as f32 <= point.y
+        && point.x <= self.bottom_right[0] as f32 && point.y <= self.bottom_right[1] as f32
+    }
+
+    /// Distance from the line segment to the box, 0 if they overlap.
+    pub fn distance_to_segment(&self, a: Vector2D, b: Vector2D) -> f32 {
+        if self.contains(a) || self.contains(b) {
+            return 0_f32;
+        }
+        let tl = Vector2D{x: self.top_left[0] as f32, y: self.top_left[1] as f32};
+        let br = Vector2D{x: self.bottom_right[0] as f32, y: self.bottom_right[1] as f32};
+        let tr = Vector2D{x: br.x, y: tl.y};
+        let bl = Vector2D{x: tl.x, y: br.y};
+        segment_distance(a, b, tl, tr)
+            .min(segment_distance(a, b, tr, br))
+            .min(segment_distance(a, b, br, bl))
+            .min(segment_distance(a, b, bl, tl))
+    }
+
+    /// Distance from the point to the box, 0 if the point is inside.
+    pub fn distance(&self, point: Vector2D) -> f32 {
+        let dx = (self.top_left[0] as f32 - point.x).max(point.x - self.bottom_right[0] as f32)
+                                                    .max(0_f32);
+        let dy = (self.top_left[1] as f32 - point.y).max(point.y - self.bottom_right[1] as f32)
+                                                    .max(0_f32);
+        libm::sqrtf(dx*dx + dy*dy)
+    }
+
+    /// The part both boxes cover, `None` if they don't overlap.
+    pub fn intersection(&self, other: &AABBox) -> Option<AABBox> {
+        let top_left = Coord::new(self.top_left[0].max(other.top_left[0]),
+                                  self.top_left[1].max(other.top_left[1]));
+        let bottom_right = Coord::new(self.bottom_right[0].min(other.bottom_right[0]),
+                                      self.bottom_right[1].min(other.bottom_right[1]));
+        if top_left[0] > bottom_right[0] || top_left[1] > bottom_right[1] {
+            return None;
+        }
+        Some(AABBox {
+            top_left,
+            bottom_right,
+        })
+    }
+}
+
+/// Pixels of a line segment with round caps and the given radius, limited to `clip`.
+pub struct CapsuleIterator {
+    start: Vector2D,
+    end: Vector2D,
+    radius: f32,
+    color: GameColor,
+    left: i32,
+    right: i32,
+    bottom: i32,
+    x: i32,
+    y: i32,
+}
+
+impl CapsuleIterator {
+    pub fn new(start: Vector2D, end: Vector2D, radius: f32, color: GameColor, clip: &AABBox)
+               -> CapsuleIterator {
+        let bounds = AABBox {
+            top_left: Coord::new(libm::floorf(start.x.min(end.x) - radius) as i32,
+                                 libm::floorf(start.y.min(end.y) - radius) as i32),
+            bottom_right: Coord::new(libm::ceilf(start.x.max(end.x) + radius) as i32,
+                                     libm::ceilf(start.y.max(end.y) + radius) as i32),
+        };
+        // an empty range if the capsule is outside of the clip box
+        let area = bounds.intersection(clip).unwrap_or(AABBox {
+            top_left: Coord::new(0, 0),
+            bottom_right: Coord::new(-1, -1),
+        });
+        let left = area.top_left[0].max(0);
+        Self {
+            start,
+            end,
+            radius,
+            color,
+            left,
+            right: area.bottom_right[0],
+            bottom: area.bottom_right[1],
+            x: left,
+            y: area.top_left[1].max(0),
+        }
+    }
+}
+
+impl Iterator for CapsuleIterator {
+    type Item = Pixel<GameColor>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.y <= self.bottom {
+            let (x, y) = (self.x, self.y);
+            self.x += 1;
+            if self.x > self.right {
+                self.x = self.left;
+                self.y += 1;
+            }
+            let p = Vector2D{x: x as f32, y: y as f32};
+            if p.distance_to_segment(self.start, self.end) <= self.radius {
+                return Some(Pixel(UnsignedCoord::new(x as u32, y as u32), self.color));
+            }
+        }
+        None
+    }
+}
+
+pub struct ImgIterator {
+    data: &'static [u8],
+    i: usize,
+    width: u32,
+    pos: Coord,
+    r: Option<u8>,
+    g: Option<u8>,
+    x: i32,
+    y: i32,
+
+}
+
+impl ImgIterator {
+    pub fn new(data: &'static [u8], width: u32, pos: Coord) -> ImgIterator {
+        Self {
+            data,
+            width,
+            pos,
+            i: 0,
+            r: None,
+            g: None,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+impl Iterator for ImgIterator {
+    type Item = Pixel<GameColor>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.i >= self.data.len() {
+                return None;
+            }
+            let item = self.data[self.i];
+            self.i += 1;
+            match (self.r, self.g) {
+                (None, _) => self.r = Some(item),
+                (Some(_), None) => self.g = Some(item),
+                (Some(ri), Some(gi)) => {
+                    let color = u32::from(ri).rotate_left(16)
+                                | u32::from(gi).rotate_left(8)
+                                | u32::from(item);
+                    let pc = Pixel(UnsignedCoord::new((self.pos[0] + self.x) as u32,
+                                                      (self.pos[1] + self.y) as u32), 
+                                   GameColor{value:color});
+                    self.x += 1;
+                    if self.x >= self.width as i32 {
+                        self.x = 0;
+                        self.y += 1;
+                    }
+                    self.r = None;
+                    self.g = None;
+                    break Some(pc);
+                }
+            }
+        }
+    }
+
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+//! On-screen keyboard for entering names. It is drawn rotated towards the seat of the player
+//! that types, so nobody has to read it upside down.
+use alloc::string::String;
+use alloc::vec::Vec;
+use embedded_graphics::{
+    prelude::*,
+    coord::Coord,
+    fonts::Font12x16,
+    primitives::Rect,
+    unsignedcoord::UnsignedCoord,
+};
+
+use crate::{HEIGHT, WIDTH};
+use crate::color::GameColor;
+use crate::theme::Theme;
+
+/// Longest name that can be entered.
+pub const NAME_LEN: usize = 8;
+pub const KEY_HEIGHT: i32 = 28;
+/// Letter rows, a row of wide keys for delete and done is below them.
+pub const ROWS: [&str; 3] = ["ABCDEFGHIJ", "KLMNOPQRST", "UVWXYZ-.!?"];
+const KEYS_PER_ROW: i32 = 10;
+
+/// Side of the board a player sits at, in the order of the player input regions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Seat {
+    Right,
+    Left,
+    Top,
+    Bottom,
+}
+
+impl Seat {
+    pub fn from_index(i: usize) -> Self {
+        match i % 4 {
+            0 => Seat::Right,
+            1 => Seat::Left,
+            2 => Seat::Top,
+            _ => Seat::Bottom,
+        }
+    }
+
+    /// Width and height of the screen as seen from the seat.
+    pub fn size(self) -> (i32, i32) {
+        match self {
+            Seat::Right | Seat::Left => (HEIGHT as i32, WIDTH as i32),
+            Seat::Top | Seat::Bottom => (WIDTH as i32, HEIGHT as i32),
+        }
+    }
+
+    /// Screen position of a point seen from the seat, the seat is at the bottom of its view.
+    pub fn to_screen(self, p: Coord) -> Coord {
+        let (w, h) = (WIDTH as i32, HEIGHT as i32);
+        match self {
+            Seat::Bottom => p,
+            Seat::Top => Coord::new(w - 1 - p[0], h - 1 - p[1]),
+            Seat::Right => Coord::new(p[1], h - 1 - p[0]),
+            Seat::Left => Coord::new(w - 1 - p[1], p[0]),
+        }
+    }
+
+    pub fn to_local(self, p: Coord) -> Coord {
+        let (w, h) = (WIDTH as i32, HEIGHT as i32);
+        match self {
+            Seat::Bottom => p,
+            Seat::Top => Coord::new(w - 1 - p[0], h - 1 - p[1]),
+            Seat::Right => Coord::new(h - 1 - p[1], p[0]),
+            Seat::Left => Coord::new(p[1], w - 1 - p[0]),
+        }
+    }
+
+    /// Draws pixels given in the view of the seat.
+    pub fn draw<D, I>(self, display: &mut D, pixels: I)
+    where
+        D: Drawing<GameColor>,
+        I: Iterator<Item = Pixel<GameColor>>,
+    {
+        display.draw(pixels.filter_map(move |Pixel(p, color)| {
+            let s = self.to_screen(Coord::new(p[0] as i32, p[1] as i32));
+            if s[0] < 0 || s[1] < 0 {
+                return None;
+            }
+            Some(Pixel(UnsignedCoord::new(s[0] as u32, s[1] as u32), color))
+        }));
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Key {
+    Char(char),
+    Delete,
+    Done,
+}
+
+/// Name entry with the keyboard at the bottom of the seat's view.
+///
+/// Like the `Editor`, it gets the first touch (or `None`) every frame. A key triggers when it
+/// is touched, holding the finger down doesn't repeat it.
+pub struct Keyboard {
+    pub text: String,
+    /// Names that can't be entered, e.g. of existing profiles.
+    taken: Vec<String>,
+    seat: Seat,
+    pressed: bool,
+    dirty: bool,
+}
+
+impl Keyboard {
+    pub fn new(seat: Seat, taken: Vec<String>) -> Self {
+        Self {
+            text: String::new(),
+            taken,
+            seat,
+            pressed: false,
+            dirty: true,
+        }
+    }
+
+    pub fn is_taken(&self) -> bool {
+        self.taken.contains(&self.text)
+    }
+
+    /// Returns `true` once done is tapped with a name entered that isn't taken.
+    pub fn touch(&mut self, touch: Option<Coord>) -> bool {
+        let point = match touch {
+            Some(p) => self.seat.to_local(p),
+            None => {
+                self.pressed = false;
+                return false;
+            },
+        };
+        if self.pressed { return false; }
+        self.pressed = true;
+        match self.key_at(point) {
+            Some(Key::Char(c)) => {
+                if self.text.len() < NAME_LEN {
+                    self.text.push(c);
+                    self.dirty = true;
+                }
+            },
+            Some(Key::Delete) => {
+                self.dirty |= self.text.pop().is_some();
+            },
+            Some(Key::Done) => return !self.text.is_empty() && !self.is_taken(),
+            None => {},
+        }
+        false
+    }
+
+    /// The key under a point in the view of the seat.
+    pub fn key_at(&self, p: Coord) -> Option<Key> {
+        let (w, h) = self.seat.size();
+        let top = h - 4 * KEY_HEIGHT;
+        if p[1] < top || p[1] >= h || p[0] < 0 || p[0] >= w {
+            return None;
+        }
+        let row = ((p[1] - top) / KEY_HEIGHT) as usize;
+        if row < ROWS.len() {
+            let col = (p[0] * KEYS_PER_ROW / w) as usize;
+            ROWS[row].chars().nth(col).map(Key::Char)
+        } else if p[0] < w / 2 {
+            Some(Key::Delete)
+        } else {
+            Some(Key::Done)
+        }
+    }
+
+    pub fn draw<D: Drawing<GameColor>>(&mut self, display: &mut D, theme: &Theme) {
+        if !self.dirty { return; }
+        let (w, h) = self.seat.size();
+        let top = h - 4 * KEY_HEIGHT;
+        let key_w = w / KEYS_PER_ROW;
+        for (row, keys) in ROWS.iter().enumerate() {
+            let y = top + row as i32 * KEY_HEIGHT;
+            for (col, c) in keys.chars().enumerate() {
+                let x = col as i32 * w / KEYS_PER_ROW;
+                let mut label = [0_u8; 4];
+                self.draw_key(display, theme, Coord::new(x, y), key_w, c.encode_utf8(&mut label));
+            }
+        }
+        let y = top + ROWS.len() as i32 * KEY_HEIGHT;
+        self.draw_key(display, theme, Coord::new(0, y), w / 2, "DEL");
+        self.draw_key(display, theme, Coord::new(w / 2, y), w / 2, "OK");
+
+        // padded, so deleted letters are overwritten
+        let field = format!("NAME: {:<1$}", format!("{}_", self.text), NAME_LEN + 1);
+        let len = (field.len() * 12) as i32;
+        self.seat.draw(display, Font12x16::render_str(&field)
+                                    .with_stroke(Some(theme.text))
+                                    .with_fill(Some(theme.background))
+                                    .translate(Coord::new((w - len) / 2, top - 2 * KEY_HEIGHT))
+                                    .into_iter());
+        let status = format!("{:<10}", if self.is_taken() { "NAME TAKEN" } else { "" });
+        self.seat.draw(display, Font12x16::render_str(&status)
+                                    .with_stroke(Some(theme.border_warning))
+                                    .with_fill(Some(theme.background))
+                                    .translate(Coord::new((w - 120) / 2, top - 3 * KEY_HEIGHT))
+                                    .into_iter());
+        self.dirty = false;
+    }
+
+    fn draw_key<D>(&self, display: &mut D, theme: &Theme, top_left: Coord, width: i32, label: &str)
+    where D: Drawing<GameColor> {
+        let bottom_right = Coord::new(top_left[0] + width - 1, top_left[1] + KEY_HEIGHT - 1);
+        self.seat.draw(display, Rect::new(top_left, bottom_right)
+                                    .with_stroke(Some(theme.text))
+                                    .into_iter());
+        let len = (label.len() * 12) as i32;
+        self.seat.draw(display, Font12x16::render_str(label)
+                                    .with_stroke(Some(theme.accent))
+                                    .with_fill(Some(theme.background))
+                                    .translate(Coord::new(top_left[0] + (width - len) / 2,
+                                                          top_left[1] + (KEY_HEIGHT - 16) / 2))
+                                    .into_iter());
+    }
+}
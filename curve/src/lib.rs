@@ -0,0 +1,37 @@
+//! Game logic of embedded curve. Nothing in here knows about the board, a frontend draws the
+//! game on any `Drawing<GameColor>` and passes in touches and random numbers.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(alloc)]
+
+#[macro_use]
+extern crate alloc;
+
+// debug output is only printed with `std`, the firmware has no console for it
+#[cfg(not(feature = "std"))]
+macro_rules! println {
+    ($($arg:tt)*) => {{ let _ = format_args!($($arg)*); }};
+}
+
+pub mod buffs;
+pub mod color;
+pub mod geometry;
+pub mod player;
+pub mod game;
+pub mod border;
+pub mod storage;
+pub mod settings;
+pub mod scoring;
+pub mod map;
+pub mod editor;
+pub mod trace;
+pub mod random;
+pub mod bot;
+pub mod spawn;
+pub mod theme;
+pub mod effects;
+pub mod keyboard;
+pub mod profile;
+
+/// Size of the display the game is laid out for.
+pub const WIDTH: usize = 480;
+pub const HEIGHT: usize = 272;
@@ -0,0 +1,424 @@
+use alloc::{
+    vec::Vec,
+    string::String,
+};
+use core::str::FromStr;
+use core::fmt::Write;
+use embedded_graphics::{
+    prelude::*,
+    coord::Coord,
+    primitives::{Circle, Line, Rect},
+};
+
+use crate::color::GameColor;
+use crate::geometry::{AABBox, Vector2D, segment_distance};
+use crate::storage::{Record, Reader, put_u16};
+
+/// Number of maps drawn in the editor that are kept in the storage.
+pub const MAX_CUSTOM_MAPS: usize = 4;
+
+/// Built-in maps, see `maps/*.map` for the format.
+pub const MAPS: [&str; 6] = [
+    include_str!("../maps/open.map"),
+    include_str!("../maps/pillars.map"),
+    include_str!("../maps/cross.map"),
+    include_str!("../maps/zigzag.map"),
+    include_str!("../maps/tube.map"),
+    include_str!("../maps/pinball.map"),
+];
+
+/// Half the thickness of a polyline wall.
+const LINE_RADIUS: f32 = 1.0;
+
+/// What happens to a curve that reaches an edge of the arena.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EdgeBehavior {
+    /// The curve comes back in on the opposite side.
+    Wrap,
+    /// The edge is a wall (like an active `Border`).
+    Wall,
+    /// The curve is reflected.
+    Bounce,
+}
+
+impl EdgeBehavior {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "wrap" => Some(EdgeBehavior::Wrap),
+            "wall" => Some(EdgeBehavior::Wall),
+            "bounce" => Some(EdgeBehavior::Bounce),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EdgeBehavior::Wrap => "wrap",
+            EdgeBehavior::Wall => "wall",
+            EdgeBehavior::Bounce => "bounce",
+        }
+    }
+}
+
+/// Behavior of each edge of the arena.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Edges {
+    pub left: EdgeBehavior,
+    pub right: EdgeBehavior,
+    pub top: EdgeBehavior,
+    pub bottom: EdgeBehavior,
+}
+
+impl Edges {
+    pub fn all(behavior: EdgeBehavior) -> Self {
+        Self {
+            left: behavior,
+            right: behavior,
+            top: behavior,
+            bottom: behavior,
+        }
+    }
+
+    pub fn any(&self, behavior: EdgeBehavior) -> bool {
+        self.left == behavior || self.right == behavior
+        || self.top == behavior || self.bottom == behavior
+    }
+
+    /// Whether opposite edges either both wrap or both don't.
+    pub fn wraps_in_pairs(&self) -> bool {
+        let wraps = |e: EdgeBehavior| e == EdgeBehavior::Wrap;
+        wraps(self.left) == wraps(self.right) && wraps(self.top) == wraps(self.bottom)
+    }
+}
+
+impl Default for Edges {
+    fn default() -> Self {
+        Self::all(EdgeBehavior::Wrap)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Obstacle {
+    Rect(AABBox),
+    Circle { center: Vector2D, radius: f32 },
+    Polyline(Vec<Vector2D>),
+}
+
+impl Obstacle {
+    /// Distance from the point to the surface of the obstacle, 0 if the point is inside.
+    pub fn distance(&self, point: Vector2D) -> f32 {
+        match self {
+            Obstacle::Rect(aabb) => aabb.distance(point),
+            Obstacle::Circle { center, radius } => (point.distance(*center) - radius).max(0_f32),
+            Obstacle::Polyline(points) => {
+                points.windows(2)
+                      .map(|w| point.distance_to_segment(w[0], w[1]))
+                      .fold(core::f32::INFINITY, f32::min)
+            },
+        }
+    }
+
+    /// Distance from the line segment `a`-`b` to the surface of the obstacle.
+    pub fn distance_to_segment(&self, a: Vector2D, b: Vector2D) -> f32 {
+        match self {
+            Obstacle::Rect(aabb) => aabb.distance_to_segment(a, b),
+            Obstacle::Circle { center, radius } => {
+                (center.distance_to_segment(a, b) - radius).max(0_f32)
+            },
+            Obstacle::Polyline(points) => {
+                points.windows(2)
+                      .map(|w| segment_distance(a, b, w[0], w[1]))
+                      .fold(core::f32::INFINITY, f32::min)
+            },
+        }
+    }
+
+    pub fn draw<D: Drawing<GameColor>>(&self, display: &mut D, color: GameColor) {
+        match self {
+            Obstacle::Rect(aabb) => {
+                display.draw(Rect::new(aabb.top_left, aabb.bottom_right)
+                                .with_stroke(Some(color))
+                                .with_fill(Some(color))
+                                .into_iter());
+            },
+            Obstacle::Circle { center, radius } => {
+                display.draw(Circle::new(to_coord(*center), *radius as u32)
+                                .with_stroke(Some(color))
+                                .with_fill(Some(color))
+                                .into_iter());
+            },
+            Obstacle::Polyline(points) => {
+                for w in points.windows(2) {
+                    display.draw(Line::new(to_coord(w[0]), to_coord(w[1]))
+                                    .with_stroke(Some(color))
+                                    .with_stroke_width(2 * LINE_RADIUS as u8)
+                                    .into_iter());
+                }
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownCommand,
+    MissingValue,
+    TooManyValues,
+    InvalidNumber,
+    /// Empty rectangle, circle without radius or polyline with less than two points.
+    InvalidShape,
+    /// An edge that is not `wrap`, `wall` or `bounce`.
+    InvalidEdge,
+    /// Only one edge of an axis wraps, a curve would come back in through a wall.
+    OneSidedWrap,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// 1-based line in the map source.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+/// An arena with static obstacles.
+///
+/// The text format has one command per line, `#` starts a comment:
+///
+/// ```text
+/// name Pillars
+/// edges wrap                  # wrap | wall | bounce, for all edges
+/// edges wrap wall             # left and right, top and bottom
+/// edges wall bounce wrap wrap # left, right, top, bottom, opposite edges wrap together
+/// rect 100 50 120 80          # top left, bottom right
+/// circle 240 136 20           # center, radius
+/// line 10 10 50 50 90 10      # polyline through all points
+/// spawn 200 100 280 170       # area players may start in
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    pub name: String,
+    pub edges: Edges,
+    pub obstacles: Vec<Obstacle>,
+    pub spawns: Vec<AABBox>,
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self {
+            name: String::from("Open"),
+            edges: Edges::default(),
+            obstacles: Vec::new(),
+            spawns: Vec::new(),
+        }
+    }
+}
+
+impl Map {
+    pub fn builtin(i: usize) -> Self {
+        Self::parse(MAPS[i % MAPS.len()]).expect("invalid built-in map")
+    }
+
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        let mut map = Map::default();
+        for (i, line) in src.lines().enumerate() {
+            let err = |kind| ParseError { line: i + 1, kind };
+            let line = line.split('#').next().unwrap().trim();
+            let mut words = line.split_whitespace();
+            let cmd = match words.next() {
+                Some(cmd) => cmd,
+                None => continue,
+            };
+            match cmd {
+                "name" => {
+                    let name = line[cmd.len()..].trim();
+                    if name.is_empty() {
+                        return Err(err(ParseErrorKind::MissingValue));
+                    }
+                    map.name = String::from(name);
+                },
+                "edges" => {
+                    let mut v = Vec::new();
+                    for word in words.by_ref() {
+                        v.push(EdgeBehavior::parse(word)
+                                   .ok_or_else(|| err(ParseErrorKind::InvalidEdge))?);
+                    }
+                    map.edges = match v.len() {
+                        0 => return Err(err(ParseErrorKind::MissingValue)),
+                        1 => Edges::all(v[0]),
+                        2 => Edges { left: v[0], right: v[0], top: v[1], bottom: v[1] },
+                        3 => return Err(err(ParseErrorKind::MissingValue)),
+                        4 => Edges { left: v[0], right: v[1], top: v[2], bottom: v[3] },
+                        _ => return Err(err(ParseErrorKind::TooManyValues)),
+                    };
+                    if !map.edges.wraps_in_pairs() {
+                        return Err(err(ParseErrorKind::OneSidedWrap));
+                    }
+                },
+                "rect" | "spawn" => {
+                    let v = numbers::<i32>(&mut words, 4).map_err(err)?;
+                    if v[0] >= v[2] || v[1] >= v[3] {
+                        return Err(err(ParseErrorKind::InvalidShape));
+                    }
+                    let aabb = AABBox::new(Coord::new(v[0], v[1]), Coord::new(v[2], v[3]));
+                    if cmd == "rect" {
+                        map.obstacles.push(Obstacle::Rect(aabb));
+                    } else {
+                        map.spawns.push(aabb);
+                    }
+                },
+                "circle" => {
+                    let v = numbers::<f32>(&mut words, 3).map_err(err)?;
+                    if v[2] <= 0_f32 {
+                        return Err(err(ParseErrorKind::InvalidShape));
+                    }
+                    map.obstacles.push(Obstacle::Circle {
+                        center: Vector2D { x: v[0], y: v[1] },
+                        radius: v[2],
+                    });
+                },
+                "line" => {
+                    let v = words.by_ref()
+                                 .map(|w| w.parse::<f32>())
+                                 .collect::<Result<Vec<f32>, _>>()
+                                 .map_err(|_| err(ParseErrorKind::InvalidNumber))?;
+                    if v.len() % 2 != 0 {
+                        return Err(err(ParseErrorKind::MissingValue));
+                    }
+                    if v.len() < 4 {
+                        return Err(err(ParseErrorKind::InvalidShape));
+                    }
+                    let points = v.chunks(2).map(|c| Vector2D { x: c[0], y: c[1] }).collect();
+                    map.obstacles.push(Obstacle::Polyline(points));
+                },
+                _ => return Err(err(ParseErrorKind::UnknownCommand)),
+            }
+            if cmd != "name" && words.next().is_some() {
+                return Err(err(ParseErrorKind::TooManyValues));
+            }
+        }
+        Ok(map)
+    }
+
+    /// Serializes the map into the text format `parse` reads.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        // writing into a `String` can't fail
+        let _ = writeln!(text, "name {}", self.name);
+        let e = self.edges;
+        if e == Edges::all(e.left) {
+            let _ = writeln!(text, "edges {}", e.left.name());
+        } else {
+            let _ = writeln!(text, "edges {} {} {} {}", e.left.name(), e.right.name(),
+                             e.top.name(), e.bottom.name());
+        }
+        for o in &self.obstacles {
+            match o {
+                Obstacle::Rect(aabb) => {
+                    let _ = writeln!(text, "rect {} {} {} {}", aabb.top_left[0], aabb.top_left[1],
+                                     aabb.bottom_right[0], aabb.bottom_right[1]);
+                },
+                Obstacle::Circle { center, radius } => {
+                    let _ = writeln!(text, "circle {} {} {}", center.x, center.y, radius);
+                },
+                Obstacle::Polyline(points) => {
+                    text.push_str("line");
+                    for p in points {
+                        let _ = write!(text, " {} {}", p.x, p.y);
+                    }
+                    text.push('\n');
+                },
+            }
+        }
+        for s in &self.spawns {
+            let _ = writeln!(text, "spawn {} {} {} {}", s.top_left[0], s.top_left[1],
+                             s.bottom_right[0], s.bottom_right[1]);
+        }
+        text
+    }
+
+    /// Distance from the point to the closest obstacle.
+    pub fn distance(&self, point: Vector2D) -> f32 {
+        self.obstacles.iter()
+                      .map(|o| o.distance(point))
+                      .fold(core::f32::INFINITY, f32::min)
+    }
+
+    pub fn draw<D: Drawing<GameColor>>(&self, display: &mut D, color: GameColor) {
+        for o in &self.obstacles {
+            o.draw(display, color);
+        }
+    }
+}
+
+fn numbers<'a, T, I>(words: &mut I, n: usize) -> Result<Vec<T>, ParseErrorKind>
+where
+    T: FromStr,
+    I: Iterator<Item = &'a str>,
+{
+    let mut v = Vec::with_capacity(n);
+    for _ in 0..n {
+        let word = words.next().ok_or(ParseErrorKind::MissingValue)?;
+        v.push(word.parse().map_err(|_| ParseErrorKind::InvalidNumber)?);
+    }
+    Ok(v)
+}
+
+fn to_coord(v: Vector2D) -> Coord {
+    Coord::new(v.x as i32, v.y as i32)
+}
+
+/// Maps drawn in the editor, stored in their text format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomMaps {
+    pub maps: Vec<String>,
+}
+
+impl CustomMaps {
+    /// Adds the map, or replaces the one with the same name. The oldest map is dropped when
+    /// there are too many.
+    pub fn add(&mut self, map: &Map) {
+        let name = map.name.clone();
+        self.maps.retain(|m| Map::parse(m).map(|m| m.name != name).unwrap_or(false));
+        if self.maps.len() >= MAX_CUSTOM_MAPS {
+            self.maps.remove(0);
+        }
+        self.maps.push(map.to_text());
+    }
+
+    pub fn parsed(&self) -> Vec<Map> {
+        self.maps.iter().filter_map(|m| Map::parse(m).ok()).collect()
+    }
+}
+
+impl Record for CustomMaps {
+    const KIND: u8 = 4;
+    const VERSION: u8 = 1;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(self.maps.len() as u8);
+        for m in &self.maps {
+            put_u16(buf, m.len() as u16);
+            buf.extend_from_slice(m.as_bytes());
+        }
+    }
+
+    fn decode(version: u8, data: &[u8]) -> Option<Self> {
+        match version {
+            1 => {
+                let mut r = Reader::new(data);
+                let mut maps = Vec::new();
+                for _ in 0..r.u8()? {
+                    let len = usize::from(r.u16()?);
+                    let mut bytes = Vec::with_capacity(len);
+                    for _ in 0..len {
+                        bytes.push(r.u8()?);
+                    }
+                    maps.push(String::from_utf8(bytes).ok()?);
+                }
+                Some(Self { maps })
+            },
+            // written by a newer firmware
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use core::fmt::Write;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Line;
+use crate::{HEIGHT, WIDTH};
+use core::f32::consts::PI;
+use heapless::{String, Vec};
+use heapless::consts::{U8, U64};
+
+use crate::geometry::{AABBox, CapsuleIterator, Vector2D, segment_distance};
+use crate::color::GameColor;
+use crate::border::Border;
+use crate::map::{Obstacle, Edges, EdgeBehavior};
+use crate::buffs::{PlayerBuff, Buff, BuffSprite};
+use crate::trace::{Segment, Trace, TraceBuffer};
+use crate::theme::Palette;
+
+pub const PAD_LEFT: f32 = 10_f32;
+pub const PAD_RIGHT: f32 = 10_f32;
+pub const PAD_BOTTOM: f32 = 10_f32;
+pub const PAD_TOP: f32 = 10_f32;
+
+// The edges of the arena, a wrapping curve crosses them at exactly these coordinates.
+pub const FIELD_LEFT: f32 = PAD_LEFT;
+pub const FIELD_RIGHT: f32 = WIDTH as f32 - PAD_RIGHT;
+pub const FIELD_TOP: f32 = PAD_TOP;
+pub const FIELD_BOTTOM: f32 = HEIGHT as f32 - PAD_BOTTOM;
+
+// A head this close to a wrapping edge can hit traces on the other side.
+const SEAM_MARGIN: f32 = 20_f32;
+// Extra length of the own trace behind the head that can't be hit.
+const SELF_GRACE: f32 = 1_f32;
+/// Degrees a curve turns per step while steering.
+const TURN_RATE: f32 = 5_f32;
+/// Distance from the head at which a buff is picked up, on top of the radius.
+const PICKUP_REACH: f32 = 10_f32;
+
+pub trait Collide<T> {
+    fn collides_with(&self, incoming: &T) -> bool;
+}
+
+pub trait CollideSelf {
+    fn collides(&self) -> bool;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlayerInput {
+    Left,
+    Right,
+    Both,
+    None
+}
+
+/// Per-player modifiers chosen before a match. They apply on top of the buffs, a slow buff on
+/// a curve with a speed of 0.8 makes it even slower.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Handicap {
+    /// Factor on the speed.
+    pub speed: f32,
+    /// Factor on how fast the curve steers.
+    pub turn: f32,
+    /// Factor on the radius of the head and the trace.
+    pub radius: f32,
+    /// Extra reach for picking up buffs, in pixels.
+    pub pickup: f32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self {
+            speed: 1_f32,
+            turn: 1_f32,
+            radius: 1_f32,
+            pickup: 0_f32,
+        }
+    }
+}
+
+impl Handicap {
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The modifiers that differ from the default, like "speed 80%, pickup +10".
+    pub fn describe(&self) -> String<U64> {
+        let mut text = String::new();
+        let percent = |f: f32| libm::roundf(f * 100_f32) as i32;
+        let factors = [("speed", self.speed), ("turn", self.turn), ("radius", self.radius)];
+        for (name, factor) in factors.iter().filter(|(_, f)| *f != 1_f32) {
+            let separator = if text.is_empty() { "" } else { ", " };
+            write!(text, "{}{} {}%", separator, name, percent(*factor)).ok();
+        }
+        if self.pickup != 0_f32 {
+            let separator = if text.is_empty() { "" } else { ", " };
+            write!(text, "{}pickup {:+}", separator, libm::roundf(self.pickup) as i32).ok();
+        }
+        text
+    }
+}
+
+pub struct Curve<'a> {
+    pos: Vector2D,
+    pub color: GameColor,
+    direction: Vector2D,
+    radius: u32,
+    speed: f32,
+    edges: Edges,
+    /// Path of the head during the last move, split where it crossed an edge.
+    path: [(Vector2D, Vector2D); 3],
+    path_len: usize,
+    buffs: Vec<PlayerBuff, U8>,
+    trace: Trace<'a>,
+    handicap: Handicap,
+}
+
+/// The whole display, traces may reach into the padding around the field.
+pub fn screen_box() -> AABBox {
+    AABBox::new(Coord::new(0, 0), Coord::new(WIDTH as i32 - 1, HEIGHT as i32 - 1))
+}
+
+/// The arena without the padding, the HUD is drawn there.
+pub fn field_box() -> AABBox {
+    AABBox::new(Coord::new(FIELD_LEFT as i32, FIELD_TOP as i32),
+                Coord::new(FIELD_RIGHT as i32, FIELD_BOTTOM as i32))
+}
+
+fn axis(v: Vector2D, axis: usize) -> f32 {
+    if axis == 0 { v.x } else { v.y }
+}
+
+fn with_axis(v: Vector2D, axis: usize, value: f32) -> Vector2D {
+    if axis == 0 { Vector2D{x: value, y: v.y} } else { Vector2D{x: v.x, y: value} }
+}
+
+impl<'a> Curve<'a> {
+    /// A curve that leaves its trace in `trace`.
+    pub fn new(color: GameColor, start_pos: (f32, f32), radius: u32, angle: f32,
+               trace: &'a mut TraceBuffer) -> Self {
+        Self::with_handicap(color, start_pos, radius, angle, Handicap::default(), trace)
+    }
+
+    pub fn with_handicap(color: GameColor, start_pos: (f32, f32), radius: u32, angle: f32,
+                         handicap: Handicap, trace: &'a mut TraceBuffer) -> Self {
+        let a = angle * PI / 180.0;
+        let pos = Vector2D {x: start_pos.0, y: start_pos.1};
+        let trace = Trace::new(pos, radius as f32 * handicap.radius, trace);
+
+        Curve {
+            pos,
+            color,
+            direction: Vector2D{x: 1.0, y: 0.0}.rotate(a),
+            speed: 1.0,
+            radius,
+            edges: Edges::default(),
+            path: [(pos, pos); 3],
+            path_len: 1,
+            buffs: Vec::new(),
+            trace,
+            handicap,
+        }
+    }
+
+    /// Starts the curve over like `with_handicap`, but in place: the trace keeps its buffer.
+    /// The radius and the edges stay as they are.
+    pub fn restart(&mut self, color: GameColor, start_pos: (f32, f32), angle: f32,
+                   handicap: Handicap) {
+        let a = angle * PI / 180.0;
+        let pos = Vector2D {x: start_pos.0, y: start_pos.1};
+        self.pos = pos;
+        self.color = color;
+        self.direction = Vector2D{x: 1.0, y: 0.0}.rotate(a);
+        self.speed = 1.0;
+        self.path = [(pos, pos); 3];
+        self.path_len = 1;
+        self.buffs.clear();
+        self.handicap = handicap;
+        self.trace.reset(pos, self.radius as f32 * handicap.radius);
+    }
+
+    /// Draws the last move of the head, `palette` holds the colors of all curves in the game.
+    pub fn draw<D: Drawing<GameColor>>(&self, display: &mut D, palette: &Palette) {
+        let color = self.buffs
+                        .iter()
+                        .fold(self.color, |acc, func| (func.change_color)(func.timeout, acc, palette));
+        let radius = self.effective_radius();
+
+        if cfg!(debug_assertions) {
+            let seg = self.trace.last().unwrap();
+            display.draw(Line::new(Coord::new(seg.start.x as i32,
+                                              seg.start.y as i32),
+                                   Coord::new(seg.end.x as i32,
+                                              seg.end.y as i32))
+                            .with_stroke(Some(GameColor{value: 0xFF_0000}))
+                            .with_fill(Some(GameColor{value: 0xFF_0000}))
+                            .into_iter() );
+        } else {
+            // the whole move, a single circle at the head leaves gaps at high speed
+            let screen = screen_box();
+            for (from, to) in self.path() {
+                display.draw(CapsuleIterator::new(*from, *to, radius, color, &screen));
+            }
+        }
+    }
+
+    /// Draws the part of the trace inside `region` in the color of the curve.
+    pub fn draw_trace<D: Drawing<GameColor>>(&self, display: &mut D, region: &AABBox) {
+        for seg in self.trace.iter() {
+            display.draw(CapsuleIterator::new(seg.start, seg.end, seg.radius, self.color, region));
+        }
+    }
+
+    /// Radius after all buffs and the handicap, used for drawing, hit tests and new trace
+    /// segments.
+    pub fn effective_radius(&self) -> f32 {
+        self.buffs
+            .iter()
+            .fold(self.radius as f32, |acc, func| (func.change_radius)(func.timeout, acc))
+            * self.handicap.radius
+    }
+
+    /// Speed after all buffs and the handicap, in pixels per step.
+    pub fn effective_speed(&self) -> f32 {
+        self.buffs
+            .iter()
+            .fold(self.speed, |acc, func| (func.change_speed)(func.timeout, acc))
+            * self.handicap.speed
+    }
+
+    pub fn handicap(&self) -> &Handicap {
+        &self.handicap
+    }
+
+    pub fn pos(&self) -> Vector2D {
+        self.pos
+    }
+
+    /// Unit vector the head is moving along.
+    pub fn direction(&self) -> Vector2D {
+        self.direction
+    }
+
+    pub fn trace(&self) -> &Trace<'a> {
+        &self.trace
+    }
+
+    pub fn set_edges(&mut self, edges: Edges) {
+        self.edges = edges;
+    }
+
+    /// Moves the head, wrapping or bouncing at the edges of the arena. Every time the head
+    /// crosses an edge the current trace segment ends exactly on it and a new one starts where
+    /// the head comes back in.
+    fn update_pos(&mut self) {
+        let speed = self.effective_speed();
+
+        let mut from = self.pos;
+        let mut to = self.pos + self.direction * speed;
+        self.path_len = 0;
+        let bounds = [(FIELD_LEFT, FIELD_RIGHT), (FIELD_TOP, FIELD_BOTTOM)];
+        let behavior = [(self.edges.left, self.edges.right),
+                        (self.edges.top, self.edges.bottom)];
+        for a in 0..2 {
+            let (min, max) = bounds[a];
+            let v = axis(to, a);
+            let (bound, edge, shift) = if v < min {
+                (min, behavior[a].0, max - min)
+            } else if v > max {
+                (max, behavior[a].1, min - max)
+            } else {
+                continue;
+            };
+            let t = (bound - axis(from, a)) / (v - axis(from, a));
+            let exit = from + (to - from) * t;
+            let entry = match edge {
+                // the curve dies there, see `Collide<Edges>`
+                EdgeBehavior::Wall => continue,
+                EdgeBehavior::Wrap => {
+                    to = with_axis(to, a, v + shift);
+                    with_axis(exit, a, bound + shift)
+                },
+                EdgeBehavior::Bounce => {
+                    to = with_axis(to, a, 2_f32 * bound - v);
+                    self.direction = with_axis(self.direction, a, -axis(self.direction, a));
+                    exit
+                },
+            };
+            let radius = self.effective_radius();
+            self.trace.advance(exit, radius);
+            self.trace.push(Segment{start: entry, end: entry, radius});
+            self.path[self.path_len] = (from, exit);
+            self.path_len += 1;
+            from = entry;
+        }
+        self.path[self.path_len] = (from, to);
+        self.path_len += 1;
+        self.pos = to;
+    }
+
+    /// Stretches the trace to the head. Whether that needs a new segment only depends on the
+    /// path, a curve turned by a buff bends its trace just like steering does.
+    fn update_trace(&mut self) {
+        // the round is over, see `Game::arena_full`
+        if self.trace.is_full() { return; }
+        let radius = self.effective_radius();
+        self.trace.advance(self.pos, radius);
+    }
+
+    fn update_buffs(&mut self) {
+        let mut i = 0;
+        while i < self.buffs.len() {
+            if self.buffs[i].timeout == 0 {
+                self.buffs.swap_remove(i);
+            } else {
+                self.buffs[i].timeout -= 1;
+                i += 1;
+            }
+        }
+    }
+
+    pub fn act(&mut self, input: PlayerInput) {
+        let mut rotation = self.buffs
+                           .iter()
+                           .fold(0_f32, |acc, func| (func.change_rotation)(func.timeout, acc))
+                           * (PI) / 180.0;
+        let steer = TURN_RATE * self.handicap.turn * (PI) / 180.0;
+        match input {
+            PlayerInput::Left => rotation -= steer,
+            PlayerInput::Right => rotation += steer,
+            _ => {},
+        }
+        self.direction = self.direction.rotate(rotation);
+        self.update_pos();
+        self.update_trace();
+        self.update_buffs();
+    }
+
+    /// Whether both heads touch while moving towards each other.
+    pub fn head_on(&self, other: &Curve) -> bool {
+        let to_other = other.pos - self.pos;
+        self.pos.distance(other.pos) < self.effective_radius() + other.effective_radius()
+            && to_other.dot(self.direction) > 0_f32
+            && to_other.dot(other.direction) < 0_f32
+    }
+
+    pub fn add_buff(&mut self, buff: PlayerBuff) {
+        // with all slots taken the buff that runs out first makes room
+        if let Err(buff) = self.buffs.push(buff) {
+            let (i, _) = self.buffs.iter()
+                                   .enumerate()
+                                   .min_by_key(|(_, b)| b.timeout)
+                                   .unwrap();
+            self.buffs[i] = buff;
+        }
+    }
+
+    pub fn clear_trace(&mut self) {
+        let radius = self.effective_radius();
+        self.trace.reset(self.pos, radius);
+    }
+
+    /// Whether the trace ran out of memory.
+    pub fn trace_full(&self) -> bool {
+        self.trace.is_full()
+    }
+
+    /// Offsets of the copies of the head behind the wrapping edges it is close to, the first
+    /// one is zero. Traces on the other side of such an edge are only a few pixels away.
+    fn seam_shifts(&self) -> ([Vector2D; 4], usize) {
+        let mut shift_x = 0_f32;
+        let mut shift_y = 0_f32;
+        if self.edges.left == EdgeBehavior::Wrap && self.pos.x - FIELD_LEFT < SEAM_MARGIN {
+            shift_x = FIELD_RIGHT - FIELD_LEFT;
+        } else if self.edges.right == EdgeBehavior::Wrap && FIELD_RIGHT - self.pos.x < SEAM_MARGIN {
+            shift_x = FIELD_LEFT - FIELD_RIGHT;
+        }
+        if self.edges.top == EdgeBehavior::Wrap && self.pos.y - FIELD_TOP < SEAM_MARGIN {
+            shift_y = FIELD_BOTTOM - FIELD_TOP;
+        } else if self.edges.bottom == EdgeBehavior::Wrap && FIELD_BOTTOM - self.pos.y < SEAM_MARGIN {
+            shift_y = FIELD_TOP - FIELD_BOTTOM;
+        }
+        let mut shifts = [Vector2D::default(); 4];
+        let mut n = 1;
+        if shift_x != 0_f32 {
+            shifts[n] = Vector2D{x: shift_x, y: 0_f32};
+            n += 1;
+        }
+        if shift_y != 0_f32 {
+            shifts[n] = Vector2D{x: 0_f32, y: shift_y};
+            n += 1;
+            if shift_x != 0_f32 {
+                shifts[n] = Vector2D{x: shift_x, y: shift_y};
+                n += 1;
+            }
+        }
+        (shifts, n)
+    }
+
+    /// The pieces of the last move of the head.
+    fn path(&self) -> &[(Vector2D, Vector2D)] {
+        &self.path[..self.path_len]
+    }
+
+    fn has_collision<I>(&self, trace: I) -> bool
+    where I: Iterator<Item = Segment> + Clone {
+        let (shifts, n) = self.seam_shifts();
+        let radius = self.effective_radius();
+        for shift in &shifts[..n] {
+            for (from, to) in self.path() {
+                for seg in trace.clone() {
+                    if sweep_collides_with_segment(*from + *shift, *to + *shift, radius, &seg) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+}
+
+/// Whether a head of the radius, swept from `from` to `to`, touches the segment. Checking the
+/// whole move instead of the new position keeps fast curves from tunnelling through traces.
+fn sweep_collides_with_segment(from: Vector2D, to: Vector2D, radius: f32, seg: &Segment) -> bool {
+    let dist = segment_distance(from, to, seg.start, seg.end);
+    if dist < radius + seg.radius {
+        if cfg!(debug_assertions) {println!("collision {} {:?}", dist, seg);}
+        return true;
+    }
+    false
+}
+
+impl<'a> CollideSelf for Curve<'a> {
+    /// The trace right behind the head always touches it. The part that is closer than the
+    /// two radii (plus the last move) along the trace is ignored, segments crossing that
+    /// distance are cut.
+    fn collides(&self) -> bool {
+        let r = self.effective_radius();
+        let moved: f32 = self.path().iter().map(|(from, to)| from.distance(*to)).sum();
+        let older = self.trace.iter().rev().scan(0_f32, move |behind, seg| {
+            let len = seg.start.distance(seg.end);
+            let grace = r + seg.radius + SELF_GRACE + moved;
+            let from_end = *behind;
+            *behind += len;
+            Some(if from_end >= grace {
+                Some(*seg)
+            } else if from_end + len > grace {
+                let t = (from_end + len - grace) / len;
+                Some(Segment{start: seg.start, end: seg.start + (seg.end - seg.start) * t,
+                             radius: seg.radius})
+            } else {
+                None
+            })
+        }).filter_map(|seg| seg);
+        self.has_collision(older)
+    }
+}
+
+impl<'a> Collide<BuffSprite> for Curve<'a> {
+    fn collides_with(&self, incoming: &BuffSprite) -> bool {
+        let b_pos = (*incoming).get_pos();
+        let b_pos = Vector2D{x: b_pos[0] as f32, y: b_pos[1] as f32};
+        self.path().iter().any(|(from, to)| {
+            self.effective_radius() + PICKUP_REACH + self.handicap.pickup
+                >= b_pos.distance_to_segment(*from, *to)
+        })
+    }
+}
+
+impl<'a, 'b> Collide<Curve<'b>> for Curve<'a> {
+    fn collides_with(&self, incoming: &Curve<'b>) -> bool {
+        self.has_collision(incoming.trace.iter().cloned())
+    }
+}
+
+impl<'a> Collide<Border> for Curve<'a> {
+    fn collides_with(&self, border: &Border) -> bool {
+
+        // the border is convex, so the ends of each piece of the path are enough
+        let r = self.effective_radius();
+        let inside = |p: Vector2D| {
+            p.x - r > border.top_left[0] as f32
+            && p.y - r > border.top_left[1] as f32
+            && p.x + r < border.bottom_right[0] as f32
+            && p.y + r < border.bottom_right[1] as f32
+        };
+        !self.path().iter().all(|(from, to)| inside(*from) && inside(*to))
+    }
+}
+
+impl<'a> Collide<Obstacle> for Curve<'a> {
+    fn collides_with(&self, obstacle: &Obstacle) -> bool {
+        self.path().iter().any(|(from, to)| {
+            obstacle.distance_to_segment(*from, *to) < self.effective_radius()
+        })
+    }
+}
+
+impl<'a> Collide<Edges> for Curve<'a> {
+    fn collides_with(&self, edges: &Edges) -> bool {
+        let r = self.effective_radius();
+        (edges.left == EdgeBehavior::Wall && self.pos.x - r < FIELD_LEFT)
+        || (edges.right == EdgeBehavior::Wall && self.pos.x + r > FIELD_RIGHT)
+        || (edges.top == EdgeBehavior::Wall && self.pos.y - r < FIELD_TOP)
+        || (edges.bottom == EdgeBehavior::Wall && self.pos.y + r > FIELD_BOTTOM)
+    }
+}
@@ -0,0 +1,108 @@
+//! Players that come back keep a name, their favourite color and seat, and their record.
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::keyboard::NAME_LEN;
+use crate::storage::{Record, Reader, put_u32};
+
+pub const MAX_PROFILES: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Index into the player colors of the theme.
+    pub color: u8,
+    /// Index of the seat (right, left, top, bottom) the player sat at last.
+    pub seat: u8,
+    pub rounds: u32,
+    pub wins: u32,
+}
+
+impl Profile {
+    pub fn new(name: &str, color: u8, seat: u8) -> Self {
+        Self {
+            name: name.chars().take(NAME_LEN).collect(),
+            color,
+            seat,
+            rounds: 0,
+            wins: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.profiles.iter().position(|p| p.name == name)
+    }
+
+    /// Adds the profile, or replaces the one with the same name. The profile with the fewest
+    /// rounds is dropped when there are too many. Returns the index of the profile.
+    pub fn add(&mut self, profile: Profile) -> usize {
+        if let Some(i) = self.find(&profile.name) {
+            self.profiles[i] = profile;
+            return i;
+        }
+        if self.profiles.len() >= MAX_PROFILES {
+            let least = (0..self.profiles.len()).min_by_key(|i| self.profiles[*i].rounds)
+                                                .unwrap_or(0);
+            self.profiles.remove(least);
+        }
+        self.profiles.push(profile);
+        self.profiles.len() - 1
+    }
+
+    /// Indices of all profiles, the ones that sat at `seat` last come first.
+    pub fn for_seat(&self, seat: u8) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.profiles.len()).collect();
+        order.sort_by_key(|i| self.profiles[*i].seat != seat);
+        order
+    }
+}
+
+impl Record for Profiles {
+    const KIND: u8 = 5;
+    const VERSION: u8 = 1;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(self.profiles.len() as u8);
+        for p in &self.profiles {
+            buf.push(p.name.len() as u8);
+            buf.extend_from_slice(p.name.as_bytes());
+            buf.push(p.color);
+            buf.push(p.seat);
+            put_u32(buf, p.rounds);
+            put_u32(buf, p.wins);
+        }
+    }
+
+    fn decode(version: u8, data: &[u8]) -> Option<Self> {
+        match version {
+            1 => {
+                let mut r = Reader::new(data);
+                let mut profiles = Vec::new();
+                for _ in 0..r.u8()? {
+                    let len = r.u8()?;
+                    let mut bytes = Vec::with_capacity(usize::from(len));
+                    for _ in 0..len {
+                        bytes.push(r.u8()?);
+                    }
+                    profiles.push(Profile {
+                        name: String::from_utf8(bytes).ok()?,
+                        color: r.u8()?,
+                        seat: r.u8()?,
+                        rounds: r.u32()?,
+                        wins: r.u32()?,
+                    });
+                }
+                Some(Self { profiles })
+            },
+            // written by a newer firmware
+            _ => None,
+        }
+    }
+}
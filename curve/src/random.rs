@@ -0,0 +1,28 @@
+/// Source of random numbers, the hardware RNG on the board.
+pub trait Random {
+    fn next_u32(&mut self) -> u32;
+}
+
+/// Small pseudo random generator for frontends and tests without a hardware RNG.
+pub struct XorShift {
+    state: u32,
+}
+
+impl XorShift {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+}
+
+impl Random for XorShift {
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
@@ -0,0 +1,140 @@
+use crate::game::{Player, DeathCause};
+
+pub const SCORING_RULES: usize = 4;
+
+const PLACEMENT_POINTS: [i32; 4] = [3, 2, 1, 0];
+
+/// Decides how many points the players get during a round.
+pub trait ScoringRule {
+    fn name(&self) -> &'static str;
+    /// Unit shown next to the score in the HUD.
+    fn unit(&self) -> &'static str { "pts" }
+    /// Called after `players[loser]` and everyone else crashing in the same step have been
+    /// marked as lost.
+    fn player_lost(&self, _players: &mut [Player], _loser: usize, _cause: DeathCause) {}
+    /// Called once when the round is over.
+    fn round_finished(&self, _players: &mut [Player]) {}
+}
+
+pub fn scoring_rule(i: usize) -> Scoring {
+    match i % SCORING_RULES {
+        0 => Scoring::Survival,
+        1 => Scoring::Kills,
+        2 => Scoring::Placement,
+        3 => Scoring::Time,
+        _ => Scoring::Survival,
+    }
+}
+
+/// The rule a match is scored by. An enum instead of `Box<ScoringRule>` keeps a game off the
+/// heap, like `BuffSprite` does for the buffs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scoring {
+    Survival,
+    Kills,
+    Placement,
+    Time,
+}
+
+impl Scoring {
+    fn rule(&self) -> &'static ScoringRule {
+        match self {
+            Scoring::Survival => &SurvivalScoring,
+            Scoring::Kills => &KillScoring,
+            Scoring::Placement => &PlacementScoring,
+            Scoring::Time => &TimeScoring,
+        }
+    }
+}
+
+impl ScoringRule for Scoring {
+    fn name(&self) -> &'static str {
+        self.rule().name()
+    }
+
+    fn unit(&self) -> &'static str {
+        self.rule().unit()
+    }
+
+    fn player_lost(&self, players: &mut [Player], loser: usize, cause: DeathCause) {
+        self.rule().player_lost(players, loser, cause)
+    }
+
+    fn round_finished(&self, players: &mut [Player]) {
+        self.rule().round_finished(players)
+    }
+}
+
+/// Every player that is still alive gets a point when someone loses.
+pub struct SurvivalScoring;
+
+impl ScoringRule for SurvivalScoring {
+    fn name(&self) -> &'static str { "Survival" }
+
+    fn player_lost(&self, players: &mut [Player], loser: usize, _cause: DeathCause) {
+        for (j, p) in players.iter_mut().enumerate() {
+            if loser != j && !p.lost { p.add_points(1); }
+        }
+    }
+}
+
+/// A point for the player whose trace was hit, minus one for running into the own trace.
+pub struct KillScoring;
+
+impl ScoringRule for KillScoring {
+    fn name(&self) -> &'static str { "Kills" }
+
+    fn unit(&self) -> &'static str { "kills" }
+
+    fn player_lost(&self, players: &mut [Player], loser: usize, cause: DeathCause) {
+        match cause {
+            DeathCause::CutOffBy(killer) if killer != loser => players[killer].add_points(1),
+            DeathCause::Suicide => players[loser].add_points(-1),
+            _ => {},
+        }
+    }
+}
+
+/// Points by the order of elimination, the last one standing gets the most.
+pub struct PlacementScoring;
+
+impl ScoringRule for PlacementScoring {
+    fn name(&self) -> &'static str { "Placement" }
+
+    fn player_lost(&self, players: &mut [Player], loser: usize, _cause: DeathCause) {
+        // everyone still alive places above the loser
+        let alive = players.iter().filter(|p| !p.lost).count();
+        let points = placement_points(players.len(), alive);
+        players[loser].add_points(points);
+    }
+
+    fn round_finished(&self, players: &mut [Player]) {
+        let points = placement_points(players.len(), 0);
+        for p in players.iter_mut().filter(|p| !p.lost) {
+            p.add_points(points);
+        }
+    }
+}
+
+/// Points for the 0-based `place` among `players`, counted from the bottom so the last place
+/// gets nothing with fewer than four players as well.
+fn placement_points(players: usize, place: usize) -> i32 {
+    let players = players.max(1).min(PLACEMENT_POINTS.len());
+    PLACEMENT_POINTS[PLACEMENT_POINTS.len() - players + place.min(players - 1)]
+}
+
+/// One point per second survived.
+pub struct TimeScoring;
+
+impl ScoringRule for TimeScoring {
+    fn name(&self) -> &'static str { "Time" }
+
+    fn unit(&self) -> &'static str { "s" }
+
+    fn round_finished(&self, players: &mut [Player]) {
+        for p in players.iter_mut() {
+            let seconds = (p.alive_ticks / 100) as i32;
+            p.add_points(seconds);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::storage::{Record, Reader, put_u16, put_u32};
+
+pub const MAX_PLAYERS: usize = 4;
+pub const HIGH_SCORE_ENTRIES: usize = 5;
+
+/// Rules of the game that can be changed without rebuilding the firmware.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub curve_radius: u32,
+    /// Upper bound of the random time between two buff spawns, in ticks.
+    pub max_tt_buff: u32,
+    pub buffs_enabled: bool,
+    /// Countdown before each round, in ticks.
+    pub ready_cooldown: u32,
+    /// Four player games are played as two teams (A and B against C and D).
+    pub teams: bool,
+    /// Curves of the same team can pass through each others traces.
+    pub friendly_trails_passable: bool,
+    /// Battle royale mode, the border closes in during the round.
+    pub shrinking_border: bool,
+    /// Ticks before the border starts to shrink.
+    pub shrink_grace: u32,
+    /// Ticks between two shrinks.
+    pub shrink_interval: u32,
+    /// Pixels the border moves inwards on every shrink.
+    pub shrink_step: u32,
+    /// Players start evenly spread on a circle instead of at random spots.
+    pub symmetric_spawns: bool,
+    /// Index into `THEMES`.
+    pub theme: u8,
+    /// The crash that ends a round is shown in slow motion.
+    pub slow_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            curve_radius: 3,
+            max_tt_buff: 100*3,
+            buffs_enabled: true,
+            ready_cooldown: 3*100,
+            teams: false,
+            friendly_trails_passable: false,
+            shrinking_border: false,
+            shrink_grace: 100*10,
+            shrink_interval: 100*5,
+            shrink_step: 10,
+            symmetric_spawns: false,
+            theme: 0,
+            slow_motion: true,
+        }
+    }
+}
+
+impl Record for Settings {
+    const KIND: u8 = 1;
+    const VERSION: u8 = 6;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(self.curve_radius as u8);
+        put_u16(buf, self.max_tt_buff as u16);
+        buf.push(self.buffs_enabled as u8);
+        put_u16(buf, self.ready_cooldown as u16);
+        buf.push(self.teams as u8);
+        buf.push(self.friendly_trails_passable as u8);
+        buf.push(self.shrinking_border as u8);
+        put_u16(buf, self.shrink_grace as u16);
+        put_u16(buf, self.shrink_interval as u16);
+        buf.push(self.shrink_step as u8);
+        buf.push(self.symmetric_spawns as u8);
+        buf.push(self.theme);
+        buf.push(self.slow_motion as u8);
+    }
+
+    fn decode(_version: u8, data: &[u8]) -> Option<Self> {
+        let d = Self::default();
+        let mut r = Reader::new(data);
+        Some(Self {
+            curve_radius: r.u8().map_or(d.curve_radius, u32::from),
+            max_tt_buff: r.u16().map_or(d.max_tt_buff, u32::from),
+            buffs_enabled: r.u8().map_or(d.buffs_enabled, |b| b != 0),
+            ready_cooldown: r.u16().map_or(d.ready_cooldown, u32::from),
+            teams: r.u8().map_or(d.teams, |b| b != 0),
+            friendly_trails_passable: r.u8().map_or(d.friendly_trails_passable, |b| b != 0),
+            shrinking_border: r.u8().map_or(d.shrinking_border, |b| b != 0),
+            shrink_grace: r.u16().map_or(d.shrink_grace, u32::from),
+            shrink_interval: r.u16().map_or(d.shrink_interval, u32::from),
+            shrink_step: r.u8().map_or(d.shrink_step, u32::from),
+            symmetric_spawns: r.u8().map_or(d.symmetric_spawns, |b| b != 0),
+            theme: r.u8().unwrap_or(d.theme),
+            slow_motion: r.u8().map_or(d.slow_motion, |b| b != 0),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    /// Profile name of the player, or "Player A" to "Player D" for guests.
+    pub name: String,
+    /// Scores add up over a whole session, so each session keeps one entry per player.
+    pub session: u32,
+}
+
+/// The best scores ever reached, highest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    pub entries: [HighScore; HIGH_SCORE_ENTRIES],
+}
+
+impl HighScores {
+    /// Replaces the entry of the player in this session if the score is good enough. Returns
+    /// the rank it got.
+    pub fn update(&mut self, score: u32, name: &str, session: u32) -> Option<usize> {
+        if let Some(old) = self.entries.iter().position(|e| e.score > 0 && e.name == name
+                                                           && e.session == session) {
+            self.entries[old..].rotate_left(1);
+            self.entries[HIGH_SCORE_ENTRIES - 1] = HighScore::default();
+        }
+        let rank = self.entries.iter().position(|e| score > e.score)?;
+        self.entries[rank..].rotate_right(1);
+        self.entries[rank] = HighScore { score, name: String::from(name), session };
+        Some(rank)
+    }
+}
+
+impl Record for HighScores {
+    const KIND: u8 = 2;
+    const VERSION: u8 = 2;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for e in &self.entries {
+            put_u32(buf, e.score);
+            buf.push(e.name.len() as u8);
+            buf.extend_from_slice(e.name.as_bytes());
+            put_u32(buf, e.session);
+        }
+    }
+
+    fn decode(version: u8, data: &[u8]) -> Option<Self> {
+        let mut high_scores = Self::default();
+        let mut r = Reader::new(data);
+        for e in high_scores.entries.iter_mut() {
+            e.score = r.u32().unwrap_or(0);
+            e.name = if version < 2 {
+                // version 1 kept the seat of the player
+                let seat = r.u8().unwrap_or(0);
+                if e.score > 0 {
+                    format!("Player {}", (b'A' + seat) as char)
+                } else {
+                    String::new()
+                }
+            } else {
+                let len = r.u8().unwrap_or(0);
+                let bytes: Option<Vec<u8>> = (0..len).map(|_| r.u8()).collect();
+                String::from_utf8(bytes.unwrap_or_default()).unwrap_or_default()
+            };
+            e.session = r.u32().unwrap_or(0);
+        }
+        Some(high_scores)
+    }
+}
+
+/// Statistics over every game ever played on this board.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub sessions: u32,
+    pub rounds_played: u32,
+    pub wins: [u32; MAX_PLAYERS],
+    pub buffs_collected: u32,
+    /// Time spent playing, in ticks.
+    pub ticks_played: u32,
+}
+
+impl Record for Stats {
+    const KIND: u8 = 3;
+    const VERSION: u8 = 1;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_u32(buf, self.sessions);
+        put_u32(buf, self.rounds_played);
+        for w in &self.wins {
+            put_u32(buf, *w);
+        }
+        put_u32(buf, self.buffs_collected);
+        put_u32(buf, self.ticks_played);
+    }
+
+    fn decode(version: u8, data: &[u8]) -> Option<Self> {
+        match version {
+            1 => {
+                let mut stats = Self::default();
+                let mut r = Reader::new(data);
+                stats.sessions = r.u32().unwrap_or(0);
+                stats.rounds_played = r.u32().unwrap_or(0);
+                for w in stats.wins.iter_mut() {
+                    *w = r.u32().unwrap_or(0);
+                }
+                stats.buffs_collected = r.u32().unwrap_or(0);
+                stats.ticks_played = r.u32().unwrap_or(0);
+                Some(stats)
+            },
+            // written by a newer firmware
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Where the curves start a round. Players keep a distance to each other and to the edges,
+//! and start heading away from walls, obstacles and other players.
+use core::f32::consts::PI;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line};
+use heapless::Vec;
+use heapless::consts::U4;
+use libm::{atan2f, cosf, sinf};
+
+use crate::border::Border;
+use crate::color::GameColor;
+use crate::game::rand_pos_in;
+use crate::geometry::{AABBox, Vector2D};
+use crate::map::{EdgeBehavior, Map};
+use crate::player::{FIELD_LEFT, FIELD_RIGHT, FIELD_TOP, FIELD_BOTTOM};
+use crate::random::Random;
+
+/// Least distance between two start points.
+pub const MIN_PLAYER_DISTANCE: f32 = 60_f32;
+/// Least distance of a start point to the edges of the field (or the active border).
+pub const MIN_EDGE_DISTANCE: f32 = 30_f32;
+/// Free space around a start point to the closest obstacle.
+pub const SPAWN_CLEARANCE: f32 = 30_f32;
+const PLACEMENT_TRIES: usize = 20;
+/// Walls, obstacles and players further away don't change the start direction.
+const AIM_RANGE: f32 = 120_f32;
+/// Random deviation from the direction away from everything, in degrees.
+const AIM_JITTER: u32 = 30;
+const ARROW_LENGTH: f32 = 25_f32;
+
+/// Start point of a curve, `angle` in degrees like `Player::reset` takes it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spawn {
+    pub pos: Vector2D,
+    pub angle: f32,
+}
+
+impl Spawn {
+    pub fn direction(&self) -> Vector2D {
+        Vector2D{x: 1_f32, y: 0_f32}.rotate(self.angle * PI / 180.0)
+    }
+
+    /// Marks the start point with an arrow in the start direction.
+    pub fn draw_arrow<D: Drawing<GameColor>>(&self, display: &mut D, color: GameColor) {
+        let dir = self.direction();
+        let tip = self.pos + dir * ARROW_LENGTH;
+        let coord = |v: Vector2D| Coord::new(v.x as i32, v.y as i32);
+        display.draw(Circle::new(coord(self.pos), 3)
+                        .with_stroke(Some(color))
+                        .with_fill(Some(color))
+                        .into_iter());
+        display.draw(Line::new(coord(self.pos), coord(tip))
+                        .with_stroke(Some(color))
+                        .into_iter());
+        for side in [-1_f32, 1_f32].iter() {
+            let barb = tip - dir.rotate(side * PI / 6.0) * 8_f32;
+            display.draw(Line::new(coord(tip), coord(barb))
+                            .with_stroke(Some(color))
+                            .into_iter());
+        }
+    }
+}
+
+/// Area the curves can move in and which of its sides (left, right, top, bottom) kill.
+fn arena(map: &Map, border: &Border) -> (AABBox, [bool; 4]) {
+    if border.active {
+        return (border.bounds(), [true; 4]);
+    }
+    let field = AABBox::new(Coord::new(FIELD_LEFT as i32, FIELD_TOP as i32),
+                            Coord::new(FIELD_RIGHT as i32, FIELD_BOTTOM as i32));
+    let e = &map.edges;
+    let wall = |edge: EdgeBehavior| edge != EdgeBehavior::Wrap;
+    (field, [wall(e.left), wall(e.right), wall(e.top), wall(e.bottom)])
+}
+
+/// Start points for `players` curves. A symmetric layout puts them evenly on a circle around
+/// the center, all circling the same way. With a crowded map the best found spots are used
+/// even if they are closer than the minimum distances.
+pub fn plan_spawns(rng: &mut Random, map: &Map, border: &Border, players: usize,
+                   symmetric: bool) -> Vec<Spawn, U4> {
+    let (bounds, walls) = arena(map, border);
+    if symmetric {
+        return symmetric_spawns(rng, map, &bounds, players);
+    }
+    let m = MIN_EDGE_DISTANCE as i32;
+    let inner = AABBox::new(Coord::new(bounds.top_left[0] + m, bounds.top_left[1] + m),
+                            Coord::new(bounds.bottom_right[0] - m, bounds.bottom_right[1] - m));
+    let mut spawns: Vec<Spawn, U4> = Vec::new();
+    for player in 0..players {
+        let zone = if map.spawns.is_empty() {
+            inner
+        } else {
+            let zone = &map.spawns[player % map.spawns.len()];
+            zone.intersection(&inner).unwrap_or(*zone)
+        };
+        let mut best = (Vector2D::default(), core::f32::NEG_INFINITY);
+        for _ in 0..PLACEMENT_TRIES {
+            let (x, y) = rand_pos_in(rng, &zone, 0);
+            let pos = Vector2D{x, y};
+            // 1 and more is good enough
+            let fit = spawns.iter()
+                            .map(|s| s.pos.distance(pos) / MIN_PLAYER_DISTANCE)
+                            .fold(map.distance(pos) / SPAWN_CLEARANCE, f32::min);
+            if fit > best.1 {
+                best = (pos, fit);
+            }
+            if fit >= 1_f32 {
+                break;
+            }
+        }
+        spawns.push(Spawn{pos: best.0, angle: 0_f32}).ok();
+    }
+    // aimed once all are placed, the first ones have to avoid the later ones too
+    for i in 0..spawns.len() {
+        spawns[i].angle = aim(rng, map, &bounds, &walls, &spawns, i);
+    }
+    spawns
+}
+
+fn symmetric_spawns(rng: &mut Random, map: &Map, bounds: &AABBox, players: usize)
+                    -> Vec<Spawn, U4> {
+    let center = Vector2D{x: (bounds.top_left[0] + bounds.bottom_right[0]) as f32 / 2_f32,
+                          y: (bounds.top_left[1] + bounds.bottom_right[1]) as f32 / 2_f32};
+    let half = ((bounds.bottom_right[0] - bounds.top_left[0])
+                    .min(bounds.bottom_right[1] - bounds.top_left[1])) as f32 / 2_f32;
+    let radius = (half - MIN_EDGE_DISTANCE).max(0_f32);
+    let layout = |offset: f32| -> Vec<Spawn, U4> {
+        (0..players).map(|i| {
+            let a = offset + 2_f32 * PI * i as f32 / players as f32;
+            Spawn {
+                pos: center + Vector2D{x: cosf(a), y: sinf(a)} * radius,
+                angle: (a + PI / 2_f32) * 180.0 / PI,
+            }
+        }).collect()
+    };
+    // the rotation of the circle that stays clearest of obstacles
+    let mut best = (0_f32, core::f32::NEG_INFINITY);
+    for _ in 0..PLACEMENT_TRIES {
+        let offset = (rng.next_u32() % 360) as f32 * PI / 180.0;
+        let clearance = layout(offset).iter()
+                                      .map(|s| map.distance(s.pos))
+                                      .fold(core::f32::INFINITY, f32::min);
+        if clearance > best.1 {
+            best = (offset, clearance);
+        }
+        if clearance > SPAWN_CLEARANCE {
+            break;
+        }
+    }
+    layout(best.0)
+}
+
+/// Start angle of spawn `i`, away from close walls, obstacles and the other spawns.
+fn aim(rng: &mut Random, map: &Map, bounds: &AABBox, walls: &[bool; 4], spawns: &[Spawn],
+       i: usize) -> f32 {
+    let pos = spawns[i].pos;
+    let mut away = Vector2D::default();
+    let sides = [
+        (walls[0], Vector2D{x: 1_f32, y: 0_f32}, pos.x - bounds.top_left[0] as f32),
+        (walls[1], Vector2D{x: -1_f32, y: 0_f32}, bounds.bottom_right[0] as f32 - pos.x),
+        (walls[2], Vector2D{x: 0_f32, y: 1_f32}, pos.y - bounds.top_left[1] as f32),
+        (walls[3], Vector2D{x: 0_f32, y: -1_f32}, bounds.bottom_right[1] as f32 - pos.y),
+    ];
+    for (wall, normal, distance) in sides.iter() {
+        if *wall && *distance < AIM_RANGE {
+            away = away + *normal * (1_f32 / distance.max(1_f32));
+        }
+    }
+    let distance = map.distance(pos);
+    if distance < AIM_RANGE {
+        // the distance grows away from the obstacle
+        let dx = Vector2D{x: 1_f32, y: 0_f32};
+        let dy = Vector2D{x: 0_f32, y: 1_f32};
+        let gradient = Vector2D{x: map.distance(pos + dx) - map.distance(pos - dx),
+                                y: map.distance(pos + dy) - map.distance(pos - dy)};
+        away = away + gradient * (0.5 / distance.max(1_f32));
+    }
+    for (j, other) in spawns.iter().enumerate() {
+        let to_self = pos - other.pos;
+        let distance = to_self.length();
+        if j != i && distance < AIM_RANGE && distance > 0_f32 {
+            away = away + to_self * (1_f32 / (distance * distance));
+        }
+    }
+    if away.length() < 1e-4 {
+        return (rng.next_u32() % 360) as f32;
+    }
+    let jitter = (rng.next_u32() % (2 * AIM_JITTER + 1)) as f32 - AIM_JITTER as f32;
+    atan2f(away.y, away.x) * 180.0 / PI + jitter
+}
@@ -0,0 +1,418 @@
+use alloc::vec::Vec;
+
+/// Raw access to a NOR-flash like memory that is split into equally sized sectors.
+///
+/// Like real flash, a write may only clear bits (1 -> 0). To set them again the whole
+/// sector has to be erased, which sets every byte to `0xFF`.
+pub trait Storage {
+    fn sector_size(&self) -> usize;
+    fn sector_count(&self) -> usize;
+    fn read(&self, sector: usize, offset: usize, buf: &mut [u8]);
+    fn write(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StorageError>;
+    fn erase(&mut self, sector: usize) -> Result<(), StorageError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageError {
+    /// The flash controller reported an error.
+    Hardware,
+    /// The record does not fit into a sector, even after compaction.
+    Full,
+    /// Simulated power loss of the `MemStorage`.
+    PowerLoss,
+}
+
+/// A typed value that can be persisted in a `RecordStore`.
+///
+/// Records written by an older firmware keep their old `VERSION`. `decode` has to accept every
+/// version up to the current one; fields missing in old records take their default values.
+pub trait Record: Sized {
+    const KIND: u8;
+    const VERSION: u8;
+    fn encode(&self, buf: &mut Vec<u8>);
+    fn decode(version: u8, data: &[u8]) -> Option<Self>;
+}
+
+/// Little endian reader for record payloads.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        let b = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        Some(u16::from(self.u8()?) | u16::from(self.u8()?) << 8)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(u32::from(self.u16()?) | u32::from(self.u16()?) << 16)
+    }
+}
+
+pub fn put_u16(buf: &mut Vec<u8>, v: u16) {
+    buf.push(v as u8);
+    buf.push((v >> 8) as u8);
+}
+
+pub fn put_u32(buf: &mut Vec<u8>, v: u32) {
+    put_u16(buf, v as u16);
+    put_u16(buf, (v >> 16) as u16);
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(0xFFFF_FFFF, data)
+}
+
+/// Feeds `data` into a running crc, so a record can be checked in pieces.
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for b in data {
+        crc ^= u32::from(*b);
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    crc
+}
+
+// Sector layout:
+//   [seq: u32][magic: u32] [record] [record] ... [0xFF ...]
+// Record layout:
+//   [kind: u8][version: u8][len: u16][payload: len bytes][crc32: u32][commit: u8]
+//
+// The magic of a sector and the commit byte of a record are written last. A write that is
+// interrupted by a power loss therefore never produces something that looks valid.
+
+const SECTOR_MAGIC: u32 = 0x4543_5256;
+const SECTOR_HEADER: usize = 8;
+const RECORD_HEADER: usize = 4;
+const RECORD_TRAILER: usize = 5;
+const COMMITTED: u8 = 0x00;
+/// Records are checked in pieces of this size, so nothing has to be allocated for it.
+const CHUNK: usize = 32;
+
+struct RawRecord {
+    kind: u8,
+    version: u8,
+    payload: Vec<u8>,
+}
+
+/// Log structured record storage on top of a `Storage`.
+///
+/// New versions of a record are appended to the active sector. When it is full, the latest
+/// version of every record is copied into the next sector, so erases are spread evenly over all
+/// sectors of the storage.
+pub struct RecordStore<S: Storage> {
+    storage: S,
+    active: usize,
+    seq: u32,
+    write_pos: usize,
+    /// Kind and position of the latest version of every record in the active sector, so the
+    /// log is only scanned by `open`.
+    latest: Vec<(u8, usize)>,
+}
+
+impl<S: Storage> RecordStore<S> {
+    pub fn open(storage: S) -> Self {
+        let mut store = Self {
+            storage,
+            active: 0,
+            seq: 0,
+            write_pos: SECTOR_HEADER,
+            latest: Vec::new(),
+        };
+        let mut found = false;
+        for sector in 0..store.storage.sector_count() {
+            if let Some(seq) = store.sector_seq(sector) {
+                if !found || seq > store.seq {
+                    store.active = sector;
+                    store.seq = seq;
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            // fresh or corrupted storage
+            let _ = store.format();
+            return store;
+        }
+        let (end, clean) = store.scan();
+        store.write_pos = end;
+        if !clean {
+            // a write was interrupted, move the intact records out of the way
+            let _ = store.compact(None);
+        }
+        store
+    }
+
+    pub fn load<R: Record>(&mut self) -> Option<R> {
+        let raw = self.read_latest(R::KIND)?;
+        let record = R::decode(raw.version, &raw.payload)?;
+        if raw.version < R::VERSION {
+            let _ = self.save(&record);
+        }
+        Some(record)
+    }
+
+    pub fn save<R: Record>(&mut self, record: &R) -> Result<(), StorageError> {
+        let mut payload = Vec::new();
+        record.encode(&mut payload);
+        if let Some(raw) = self.read_latest(R::KIND) {
+            if raw.version == R::VERSION && raw.payload == payload {
+                return Ok(());
+            }
+        }
+        let raw = RawRecord {
+            kind: R::KIND,
+            version: R::VERSION,
+            payload,
+        };
+        let size = RECORD_HEADER + raw.payload.len() + RECORD_TRAILER;
+        if SECTOR_HEADER + size > self.storage.sector_size() {
+            return Err(StorageError::Full);
+        }
+        if self.write_pos + size > self.storage.sector_size() {
+            return self.compact(Some(raw));
+        }
+        let pos = self.write_pos;
+        self.write_pos += size;
+        match self.write_record(self.active, pos, &raw) {
+            Ok(()) => {
+                set_latest(&mut self.latest, raw.kind, pos);
+                Ok(())
+            },
+            Err(err) => {
+                // nothing may be appended behind a broken record, compact on the next save
+                self.write_pos = self.storage.sector_size();
+                Err(err)
+            },
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    fn sector_seq(&self, sector: usize) -> Option<u32> {
+        let mut header = [0u8; SECTOR_HEADER];
+        self.storage.read(sector, 0, &mut header);
+        let mut reader = Reader::new(&header);
+        let seq = reader.u32()?;
+        if reader.u32()? == SECTOR_MAGIC {
+            Some(seq)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the latest valid version of every record in the active sector. Returns the end of
+    /// the log and whether the log ended cleanly.
+    fn scan(&mut self) -> (usize, bool) {
+        self.latest.clear();
+        let size = self.storage.sector_size();
+        let mut pos = SECTOR_HEADER;
+        while pos + RECORD_HEADER <= size {
+            let mut header = [0u8; RECORD_HEADER];
+            self.storage.read(self.active, pos, &mut header);
+            if header.iter().all(|b| *b == 0xFF) {
+                return (pos, true);
+            }
+            let len = usize::from(Reader::new(&header[2..]).u16().unwrap());
+            let end = pos + RECORD_HEADER + len + RECORD_TRAILER;
+            if end > size {
+                return (pos, false);
+            }
+            let crc_pos = pos + RECORD_HEADER + len;
+            let mut crc = crc32_update(0xFFFF_FFFF, &header);
+            let mut chunk = [0u8; CHUNK];
+            let mut chunk_pos = pos + RECORD_HEADER;
+            while chunk_pos < crc_pos {
+                let n = CHUNK.min(crc_pos - chunk_pos);
+                self.storage.read(self.active, chunk_pos, &mut chunk[..n]);
+                crc = crc32_update(crc, &chunk[..n]);
+                chunk_pos += n;
+            }
+            let mut trailer = [0u8; RECORD_TRAILER];
+            self.storage.read(self.active, crc_pos, &mut trailer);
+            let stored_crc = Reader::new(&trailer).u32().unwrap();
+            if trailer[4] != COMMITTED || stored_crc != !crc {
+                return (pos, false);
+            }
+            set_latest(&mut self.latest, header[0], pos);
+            pos = end;
+        }
+        (pos, true)
+    }
+
+    /// The latest version of the record of `kind`, if there is one.
+    fn read_latest(&self, kind: u8) -> Option<RawRecord> {
+        let &(_, pos) = self.latest.iter().find(|(k, _)| *k == kind)?;
+        Some(self.read_record(self.active, pos))
+    }
+
+    /// Reads a record that `scan` or `save` found to be intact.
+    fn read_record(&self, sector: usize, pos: usize) -> RawRecord {
+        let mut header = [0u8; RECORD_HEADER];
+        self.storage.read(sector, pos, &mut header);
+        let len = usize::from(Reader::new(&header[2..]).u16().unwrap());
+        let mut payload = vec![0u8; len];
+        self.storage.read(sector, pos + RECORD_HEADER, &mut payload);
+        RawRecord {
+            kind: header[0],
+            version: header[1],
+            payload,
+        }
+    }
+
+    fn write_record(&mut self, sector: usize, pos: usize, raw: &RawRecord)
+                    -> Result<(), StorageError> {
+        let mut data = Vec::with_capacity(RECORD_HEADER + raw.payload.len() + RECORD_TRAILER);
+        data.push(raw.kind);
+        data.push(raw.version);
+        put_u16(&mut data, raw.payload.len() as u16);
+        data.extend_from_slice(&raw.payload);
+        let crc = crc32(&data);
+        put_u32(&mut data, crc);
+        self.storage.write(sector, pos, &data)?;
+        self.storage.write(sector, pos + data.len(), &[COMMITTED])
+    }
+
+    /// Copies the latest version of every record into the next sector, with `new` replacing
+    /// the record of its kind.
+    fn compact(&mut self, new: Option<RawRecord>) -> Result<(), StorageError> {
+        let mut records: Vec<RawRecord> = self.latest.iter()
+            .filter(|(kind, _)| new.as_ref().map_or(true, |r| r.kind != *kind))
+            .map(|&(_, pos)| self.read_record(self.active, pos))
+            .collect();
+        records.extend(new);
+        let next = (self.active + 1) % self.storage.sector_count();
+        self.storage.erase(next)?;
+        let mut latest = Vec::with_capacity(records.len());
+        let mut pos = SECTOR_HEADER;
+        for raw in &records {
+            let size = RECORD_HEADER + raw.payload.len() + RECORD_TRAILER;
+            if pos + size > self.storage.sector_size() {
+                return Err(StorageError::Full);
+            }
+            self.write_record(next, pos, raw)?;
+            latest.push((raw.kind, pos));
+            pos += size;
+        }
+        self.write_sector_header(next, self.seq.wrapping_add(1))?;
+        self.active = next;
+        self.seq = self.seq.wrapping_add(1);
+        self.write_pos = pos;
+        self.latest = latest;
+        Ok(())
+    }
+
+    fn format(&mut self) -> Result<(), StorageError> {
+        self.storage.erase(0)?;
+        self.write_sector_header(0, 0)?;
+        self.active = 0;
+        self.seq = 0;
+        self.write_pos = SECTOR_HEADER;
+        self.latest.clear();
+        Ok(())
+    }
+
+    fn write_sector_header(&mut self, sector: usize, seq: u32) -> Result<(), StorageError> {
+        let mut buf = Vec::new();
+        put_u32(&mut buf, seq);
+        self.storage.write(sector, 0, &buf)?;
+        buf.clear();
+        put_u32(&mut buf, SECTOR_MAGIC);
+        self.storage.write(sector, 4, &buf)
+    }
+}
+
+/// Remembers `pos` as the latest version of the record of `kind`.
+fn set_latest(latest: &mut Vec<(u8, usize)>, kind: u8, pos: usize) {
+    match latest.iter_mut().find(|(k, _)| *k == kind) {
+        Some(entry) => entry.1 = pos,
+        None => latest.push((kind, pos)),
+    }
+}
+
+/// RAM backed `Storage` with flash semantics, used to run the record store on the host.
+pub struct MemStorage {
+    data: Vec<u8>,
+    sector_size: usize,
+    erase_counts: Vec<u32>,
+    write_budget: Option<usize>,
+}
+
+impl MemStorage {
+    pub fn new(sector_size: usize, sector_count: usize) -> Self {
+        Self {
+            data: vec![0xFF; sector_size * sector_count],
+            sector_size,
+            erase_counts: vec![0; sector_count],
+            write_budget: None,
+        }
+    }
+
+    /// Simulates a power loss after `bytes` more bytes have been written.
+    pub fn cut_power_after(&mut self, bytes: usize) {
+        self.write_budget = Some(bytes);
+    }
+
+    pub fn restore_power(&mut self) {
+        self.write_budget = None;
+    }
+
+    pub fn erase_counts(&self) -> &[u32] {
+        &self.erase_counts
+    }
+}
+
+impl Storage for MemStorage {
+    fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn sector_count(&self) -> usize {
+        self.erase_counts.len()
+    }
+
+    fn read(&self, sector: usize, offset: usize, buf: &mut [u8]) {
+        let start = sector * self.sector_size + offset;
+        buf.copy_from_slice(&self.data[start..start + buf.len()]);
+    }
+
+    fn write(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+        let start = sector * self.sector_size + offset;
+        for (i, b) in data.iter().enumerate() {
+            if let Some(budget) = self.write_budget {
+                if budget == 0 {
+                    return Err(StorageError::PowerLoss);
+                }
+                self.write_budget = Some(budget - 1);
+            }
+            self.data[start + i] &= *b;
+        }
+        Ok(())
+    }
+
+    fn erase(&mut self, sector: usize) -> Result<(), StorageError> {
+        if self.write_budget == Some(0) {
+            return Err(StorageError::PowerLoss);
+        }
+        let start = sector * self.sector_size;
+        for b in &mut self.data[start..start + self.sector_size] {
+            *b = 0xFF;
+        }
+        self.erase_counts[sector] += 1;
+        Ok(())
+    }
+}
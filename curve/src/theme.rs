@@ -0,0 +1,108 @@
+//! Color sets for everything drawn in the game. The colorblind themes use the palette by Okabe
+//! and Ito, the players stay apart by brightness and by blue against orange/yellow.
+use crate::color::GameColor;
+use crate::settings::MAX_PLAYERS;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: GameColor,
+    /// Menus, messages and the HUD.
+    pub text: GameColor,
+    /// Highlights like the countdown, selected tools and spawn zones.
+    pub accent: GameColor,
+    pub border: GameColor,
+    /// The border blinks in this color before it shrinks.
+    pub border_warning: GameColor,
+    pub obstacle: GameColor,
+    pub wall_edge: GameColor,
+    pub bounce_edge: GameColor,
+    pub players: Palette,
+    /// Used instead of `players` in team games, A and B against C and D.
+    pub teams: Palette,
+}
+
+/// Colors of all curves, one for each player.
+pub type Palette = [GameColor; MAX_PLAYERS];
+
+const fn c(value: u32) -> GameColor {
+    GameColor{value}
+}
+
+pub const THEMES: [Theme; 4] = [
+    Theme {
+        name: "Classic",
+        background: c(0x00_0000),
+        text: c(0xFF_FFFF),
+        accent: c(0x00_00FF),
+        border: c(0xFF_FFFF),
+        border_warning: c(0xFF_0000),
+        obstacle: c(0x80_8080),
+        wall_edge: c(0xFF_FFFF),
+        bounce_edge: c(0x00_FFFF),
+        players: [c(0x00_00FF), c(0x00_FF00), c(0xFF_0000), c(0xFF_FF00)],
+        teams: [c(0x00_00FF), c(0x00_AAFF), c(0xFF_0000), c(0xFF_AA00)],
+    },
+    Theme {
+        name: "Neon",
+        background: c(0x10_0020),
+        text: c(0xFF_FFFF),
+        accent: c(0xFF_00FF),
+        border: c(0x00_FFFF),
+        border_warning: c(0xFF_00FF),
+        obstacle: c(0x50_3070),
+        wall_edge: c(0x00_FFFF),
+        bounce_edge: c(0xFF_00FF),
+        players: [c(0x00_FFFF), c(0xFF_00FF), c(0x39_FF14), c(0xFF_FF00)],
+        teams: [c(0x00_FFFF), c(0x00_80FF), c(0xFF_00FF), c(0xFF_60A0)],
+    },
+    // red and green look alike, no player depends on telling them apart
+    Theme {
+        name: "Deutan",
+        background: c(0x00_0000),
+        text: c(0xFF_FFFF),
+        accent: c(0x56_B4E9),
+        border: c(0xFF_FFFF),
+        border_warning: c(0xE6_9F00),
+        obstacle: c(0x70_7070),
+        wall_edge: c(0xFF_FFFF),
+        bounce_edge: c(0xCC_79A7),
+        players: [c(0x00_72B2), c(0xE6_9F00), c(0x56_B4E9), c(0xF0_E442)],
+        teams: [c(0x00_72B2), c(0x56_B4E9), c(0xD5_5E00), c(0xE6_9F00)],
+    },
+    // red looks dark, every color is bright enough to see on black
+    Theme {
+        name: "Protan",
+        background: c(0x00_0000),
+        text: c(0xFF_FFFF),
+        accent: c(0x56_B4E9),
+        border: c(0xFF_FFFF),
+        border_warning: c(0xF0_E442),
+        obstacle: c(0x70_7070),
+        wall_edge: c(0xFF_FFFF),
+        bounce_edge: c(0x56_B4E9),
+        players: [c(0x00_72B2), c(0xF0_E442), c(0x56_B4E9), c(0xCC_79A7)],
+        teams: [c(0x00_72B2), c(0x56_B4E9), c(0xE6_9F00), c(0xF0_E442)],
+    },
+];
+
+/// Built-in theme `i`, wraps around like `Map::builtin`.
+pub fn theme(i: usize) -> &'static Theme {
+    &THEMES[i % THEMES.len()]
+}
+
+impl Theme {
+    /// Colors of the curves, the team colors in team games.
+    pub fn player_colors(&self, teams: bool) -> &Palette {
+        if teams { &self.teams } else { &self.players }
+    }
+}
+
+/// The color after `color` in the palette of the curves (see `Theme::player_colors`), the
+/// color buff disguises a curve as another player.
+pub fn next_player_color(palette: &Palette, color: GameColor) -> GameColor {
+    match palette.iter().position(|c| *c == color) {
+        Some(i) => palette[(i + 1) % MAX_PLAYERS],
+        None => palette[0],
+    }
+}
@@ -0,0 +1,142 @@
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use heapless::Vec;
+use heapless::consts::U1024;
+use typenum::Unsigned;
+
+use crate::geometry::Vector2D;
+use crate::settings::MAX_PLAYERS;
+
+/// Memory the traces of all players together may use, in bytes.
+pub const TRACE_BUDGET: usize = 96 * 1024;
+/// Segments a single trace can hold.
+pub type TraceCapacity = U1024;
+pub const TRACE_CAPACITY: usize = TraceCapacity::USIZE;
+/// Storage for the segments of one trace.
+pub type TraceBuffer = Vec<Segment, TraceCapacity>;
+// fails to compile if the buffers of all players don't fit into the budget
+#[allow(dead_code)]
+const TRACE_POOL_FITS: [(); 0] = [(); (mem::size_of::<TracePool>() > TRACE_BUDGET) as usize];
+/// How far a merged segment may be away from the path the head really took.
+pub const SIMPLIFY_TOLERANCE: f32 = 0.5;
+/// Bends that can be merged into one segment.
+const MAX_MERGED: usize = 16;
+/// A bend smaller than that doesn't have to be remembered.
+const STRAIGHT: f32 = 0.01;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Segment {
+    pub start: Vector2D,
+    pub end: Vector2D,
+    pub radius: f32,
+}
+
+/// The trace buffers of all players, the one at index `i` belongs to player `i`.
+///
+/// The firmware keeps the pool in a static and every game borrows it, so neither the game nor
+/// its traces take up stack or heap.
+pub struct TracePool {
+    buffers: [TraceBuffer; MAX_PLAYERS],
+}
+
+impl TracePool {
+    pub const fn new() -> Self {
+        Self {
+            buffers: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+        }
+    }
+
+    /// The buffers in player order.
+    pub fn buffers(&mut self) -> core::slice::IterMut<TraceBuffer> {
+        self.buffers.iter_mut()
+    }
+}
+
+/// The segments a curve left behind, in a buffer of `TRACE_CAPACITY` segments that it borrows.
+///
+/// Bends that hardly change the direction don't start a new segment, the last one is stretched
+/// instead as long as every bend since its start stays within `SIMPLIFY_TOLERANCE`.
+pub struct Trace<'a> {
+    segments: &'a mut TraceBuffer,
+    /// Points where the path bent on the last segment.
+    merged: [Vector2D; MAX_MERGED],
+    merged_len: usize,
+    full: bool,
+}
+
+impl<'a> Trace<'a> {
+    /// A trace that starts at `start`, whatever `buffer` held before is dropped.
+    pub fn new(start: Vector2D, radius: f32, buffer: &'a mut TraceBuffer) -> Self {
+        let mut trace = Self {
+            segments: buffer,
+            merged: [Vector2D::default(); MAX_MERGED],
+            merged_len: 0,
+            full: false,
+        };
+        trace.reset(start, radius);
+        trace
+    }
+
+    /// Removes all segments, the trace starts again at `start`.
+    pub fn reset(&mut self, start: Vector2D, radius: f32) {
+        self.segments.clear();
+        self.full = false;
+        self.push(Segment{start, end: start, radius});
+    }
+
+    /// Whether a segment couldn't be added because the buffer is used up.
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Starts a new segment, unless the buffer is full.
+    pub fn push(&mut self, segment: Segment) {
+        if self.segments.push(segment).is_err() {
+            self.full = true;
+            return;
+        }
+        self.merged_len = 0;
+    }
+
+    /// Moves the end of the trace to `end`. The last segment is stretched if the path since
+    /// its start stays close enough to it, a new segment begins otherwise. A different radius
+    /// always starts a new segment.
+    pub fn advance(&mut self, end: Vector2D, radius: f32) {
+        let last = *self.segments.last().unwrap();
+        let bend = last.end.distance_to_segment(last.start, end);
+        let merge = libm::fabsf(last.radius - radius) <= 0.01
+            && bend <= SIMPLIFY_TOLERANCE
+            && self.fits(last.start, end)
+            && (bend < STRAIGHT || self.merged_len < MAX_MERGED);
+        if !merge {
+            self.push(Segment{start: last.end, end, radius});
+            return;
+        }
+        // points on the straight line stay close enough as long as the end does
+        if bend >= STRAIGHT {
+            self.merged[self.merged_len] = last.end;
+            self.merged_len += 1;
+        }
+        self.segments.last_mut().unwrap().end = end;
+    }
+
+    fn fits(&self, start: Vector2D, end: Vector2D) -> bool {
+        self.merged[..self.merged_len]
+            .iter()
+            .all(|p| p.distance_to_segment(start, end) <= SIMPLIFY_TOLERANCE)
+    }
+}
+
+impl<'a> Deref for Trace<'a> {
+    type Target = [Segment];
+
+    fn deref(&self) -> &[Segment] {
+        &self.segments
+    }
+}
+
+impl<'a> DerefMut for Trace<'a> {
+    fn deref_mut(&mut self) -> &mut [Segment] {
+        &mut self.segments
+    }
+}
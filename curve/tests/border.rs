@@ -0,0 +1,25 @@
+use curve::border::{Border, Shrink};
+
+#[test]
+fn border_warns_before_it_shrinks() {
+    let mut border = Border::with_shrink(Shrink { grace: 300, interval: 300, step: 50 });
+    assert!(!border.update(50));
+    assert!(!border.is_warning());
+    assert!(!border.update(100));
+    assert!(border.is_warning());
+    assert!(border.update(151));
+    assert!(!border.is_warning());
+}
+
+#[test]
+fn smallest_border_stops_warning() {
+    let mut border = Border::with_shrink(Shrink { grace: 300, interval: 300, step: 50 });
+    assert!(border.update(301));
+    let bounds = border.bounds();
+    // the next step would leave less than the minimum size
+    for _ in 0..1000 {
+        assert!(!border.update(1));
+        assert!(!border.is_warning());
+    }
+    assert_eq!(border.bounds(), bounds);
+}
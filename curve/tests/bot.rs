@@ -0,0 +1,38 @@
+mod common;
+
+use curve::bot::bot_input;
+use curve::game::Game;
+use curve::map::{EdgeBehavior, Edges, Map};
+use curve::player::{PlayerInput, FIELD_RIGHT};
+use curve::random::XorShift;
+use curve::scoring::scoring_rule;
+use curve::settings::Settings;
+use curve::trace::TracePool;
+
+fn walled_game(traces: &mut TracePool) -> Game {
+    let mut rng = XorShift::new(42);
+    let mut map = Map::default();
+    map.edges = Edges::all(EdgeBehavior::Wall);
+    let mut game = Game::new(&common::COLORS[..2], Settings::default(), scoring_rule(0), map,
+                             &mut rng, traces);
+    game.new_game(&mut rng);
+    game
+}
+
+#[test]
+fn bot_turns_away_from_a_wall() {
+    let mut traces = TracePool::new();
+    let mut game = walled_game(&mut traces);
+    game.players[0].reset((FIELD_RIGHT - 20.0, 136.0), 0.0);
+    game.players[1].reset((100.0, 136.0), 180.0);
+    assert_ne!(bot_input(&game, 0), PlayerInput::None);
+}
+
+#[test]
+fn bot_goes_straight_in_free_space() {
+    let mut traces = TracePool::new();
+    let mut game = walled_game(&mut traces);
+    game.players[0].reset((FIELD_RIGHT - 20.0, 136.0), 0.0);
+    game.players[1].reset((200.0, 136.0), 180.0);
+    assert_eq!(bot_input(&game, 1), PlayerInput::None);
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use curve::buffs::{
+    Buff, BuffSprite, BigBuffSprite, ClearBuffSprite, FastPlayerBuffSprite, SlowBuffSprite,
+    SwapBuffSprite,
+};
+use curve::game::Game;
+use curve::geometry::{AABBox, Vector2D};
+use curve::map::{Map, Obstacle};
+use curve::player::PlayerInput;
+use curve::random::XorShift;
+use curve::scoring::scoring_rule;
+use curve::settings::Settings;
+use curve::trace::TracePool;
+use embedded_graphics::coord::Coord;
+
+fn at() -> Coord {
+    Coord::new(0, 0)
+}
+
+/// A game with a single curve on `map`.
+fn game<'a>(rng: &mut XorShift, map: Map, traces: &'a mut TracePool) -> Game<'a> {
+    Game::new(&common::COLORS[..1], Settings::default(), scoring_rule(0), map, rng, traces)
+}
+
+#[test]
+fn fast_buff_only_speeds_up_the_collector() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 2);
+    FastPlayerBuffSprite::new(at()).apply_players(&mut players, 0);
+    let before: Vec<_> = players.iter().map(|p| p.curve.pos()).collect();
+    for p in &mut players {
+        p.curve.act(PlayerInput::None);
+    }
+    assert!((players[0].curve.pos().x - before[0].x - 2.0).abs() < 0.01);
+    assert!((players[1].curve.pos().x - before[1].x - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn slow_buff_halves_the_speed() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 1);
+    SlowBuffSprite::new(at()).apply_players(&mut players, 0);
+    let before = players[0].curve.pos();
+    players[0].curve.act(PlayerInput::None);
+    assert!((players[0].curve.pos().x - before.x - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn big_buff_makes_the_trace_thicker() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 1);
+    let radius = players[0].curve.effective_radius();
+    BigBuffSprite::new(at()).apply_players(&mut players, 0);
+    assert!(players[0].curve.effective_radius() > radius);
+    for _ in 0..5 {
+        players[0].curve.act(PlayerInput::None);
+    }
+    let last = players[0].curve.trace().last().unwrap();
+    assert_eq!(last.radius, players[0].curve.effective_radius());
+}
+
+#[test]
+fn clear_buff_removes_all_traces() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 3);
+    for _ in 0..20 {
+        for p in &mut players {
+            p.curve.act(PlayerInput::Left);
+        }
+    }
+    let clear = BuffSprite::Clear(ClearBuffSprite::new(at()));
+    assert!(clear.clear_screen());
+    clear.apply_players(&mut players, 1);
+    for p in &players {
+        assert_eq!(p.curve.trace().len(), 1);
+        let seg = p.curve.trace()[0];
+        assert_eq!(seg.start, p.curve.pos());
+        assert_eq!(seg.end, p.curve.pos());
+    }
+}
+
+#[test]
+fn swap_buff_rotates_curves_but_keeps_colors() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 3);
+    let positions: Vec<_> = players.iter().map(|p| p.curve.pos()).collect();
+    let colors: Vec<_> = players.iter().map(|p| p.curve.color).collect();
+    let swap = BuffSprite::Swap(SwapBuffSprite::new(at()));
+    assert!(swap.recolors_traces());
+    swap.apply_players(&mut players, 0);
+    for i in 0..3 {
+        assert_eq!(players[i].curve.pos(), positions[(i + 1) % 3]);
+        assert_eq!(players[i].curve.color, colors[i]);
+    }
+}
+
+#[test]
+fn swap_buff_skips_players_that_lost() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 3);
+    players[1].lost = true;
+    let positions: Vec<_> = players.iter().map(|p| p.curve.pos()).collect();
+    SwapBuffSprite::new(at()).apply_players(&mut players, 0);
+    assert_eq!(players[0].curve.pos(), positions[2]);
+    assert_eq!(players[1].curve.pos(), positions[1]);
+    assert_eq!(players[2].curve.pos(), positions[0]);
+}
+
+#[test]
+fn buffs_stay_off_traces() {
+    let mut rng = XorShift::new(7);
+    let mut traces = TracePool::new();
+    let mut game = game(&mut rng, Map::default(), &mut traces);
+    game.new_game(&mut rng);
+    let head = game.players[0].curve.pos();
+    let behind = head + game.players[0].curve.direction() * -30.0;
+    assert!(game.buff_spot_taken(head));
+    assert!(!game.buff_spot_taken(behind));
+}
+
+#[test]
+fn buffs_keep_a_gap_between_them() {
+    let mut rng = XorShift::new(7);
+    let mut traces = TracePool::new();
+    let mut game = game(&mut rng, Map::default(), &mut traces);
+    game.new_game(&mut rng);
+    assert!(game.place_buff(&mut rng));
+    let pos = game.buffs()[0].get_pos();
+    let center = Vector2D{x: (pos[0] + 5) as f32, y: (pos[1] + 5) as f32};
+    // look away from the curve, so only the buff is close
+    let side = if center.x >= game.players[0].curve.pos().x { 1.0 } else { -1.0 };
+    assert!(game.buff_spot_taken(center + Vector2D{x: 13.0 * side, y: 0.0}));
+    assert!(!game.buff_spot_taken(center + Vector2D{x: 14.0 * side, y: 0.0}));
+}
+
+#[test]
+fn buffs_avoid_the_path_ahead_of_living_curves() {
+    let mut rng = XorShift::new(7);
+    let mut traces = TracePool::new();
+    let mut game = game(&mut rng, Map::default(), &mut traces);
+    game.new_game(&mut rng);
+    let head = game.players[0].curve.pos();
+    let direction = game.players[0].curve.direction();
+    assert!(game.ahead_of_curve(head + direction * 30.0));
+    assert!(!game.ahead_of_curve(head + direction * -30.0));
+    game.players[0].lost = true;
+    assert!(!game.ahead_of_curve(head + direction * 30.0));
+}
+
+#[test]
+fn crowded_field_gets_no_buff() {
+    let mut rng = XorShift::new(7);
+    let mut map = Map::default();
+    map.obstacles.push(Obstacle::Rect(AABBox::new(Coord::new(0, 0), Coord::new(479, 271))));
+    let mut traces = TracePool::new();
+    let mut game = game(&mut rng, map, &mut traces);
+    assert!(!game.place_buff(&mut rng));
+    assert!(game.buffs().is_empty());
+}
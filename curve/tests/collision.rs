@@ -0,0 +1,113 @@
+use curve::color::GameColor;
+use curve::buffs::{Buff, FastPlayerBuffSprite};
+use curve::player::{Curve, Collide, CollideSelf, PlayerInput};
+use curve::trace::TraceBuffer;
+use embedded_graphics::coord::Coord;
+
+const C: GameColor = GameColor{value: 0xFF_FFFF};
+
+fn straight(curve: &mut Curve, steps: usize) {
+    for _ in 0..steps {
+        curve.act(PlayerInput::None);
+    }
+}
+
+#[test]
+fn crossing_a_trace_is_a_collision() {
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (100.0, 100.0), 3, 0.0, &mut trace_a);
+    straight(&mut a, 100);
+    let mut trace_b = TraceBuffer::new();
+    let mut b = Curve::new(C, (150.0, 60.0), 3, 90.0, &mut trace_b);
+    let mut hit_at = None;
+    for step in 0..60 {
+        b.act(PlayerInput::None);
+        if b.collides_with(&a) {
+            hit_at = Some(step);
+            break;
+        }
+    }
+    let step = hit_at.expect("no collision");
+    // the heads touch the trace 6 pixels before the center line
+    assert!(step >= 30 && step <= 40, "collision after {} steps", step);
+}
+
+#[test]
+fn parallel_traces_dont_collide() {
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (100.0, 100.0), 3, 0.0, &mut trace_a);
+    let mut trace_b = TraceBuffer::new();
+    let mut b = Curve::new(C, (100.0, 110.0), 3, 0.0, &mut trace_b);
+    for _ in 0..200 {
+        a.act(PlayerInput::None);
+        b.act(PlayerInput::None);
+        assert!(!a.collides_with(&b));
+        assert!(!b.collides_with(&a));
+    }
+}
+
+#[test]
+fn a_straight_curve_doesnt_hit_itself() {
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (20.0, 100.0), 3, 0.0, &mut trace_a);
+    for _ in 0..300 {
+        a.act(PlayerInput::None);
+        assert!(!a.collides());
+    }
+}
+
+#[test]
+fn a_full_circle_hits_the_own_trace() {
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (200.0, 150.0), 3, 0.0, &mut trace_a);
+    let mut hit_at = None;
+    for step in 0..100 {
+        a.act(PlayerInput::Left);
+        if a.collides() {
+            hit_at = Some(step);
+            break;
+        }
+    }
+    // a circle takes 72 steps
+    let step = hit_at.expect("no self collision");
+    assert!(step > 40 && step < 80, "self collision after {} steps", step);
+}
+
+#[test]
+fn heads_moving_towards_each_other_crash() {
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (100.0, 100.0), 3, 0.0, &mut trace_a);
+    let mut trace_b = TraceBuffer::new();
+    let mut b = Curve::new(C, (120.0, 100.0), 3, 180.0, &mut trace_b);
+    let mut crashed = false;
+    for _ in 0..10 {
+        a.act(PlayerInput::None);
+        b.act(PlayerInput::None);
+        if a.head_on(&b) {
+            assert!(b.head_on(&a));
+            crashed = true;
+            break;
+        }
+    }
+    assert!(crashed);
+}
+
+#[test]
+fn fast_curves_dont_tunnel_through_thin_traces() {
+    let mut trace_wall = TraceBuffer::new();
+    let mut wall = Curve::new(C, (150.0, 50.0), 1, 90.0, &mut trace_wall);
+    straight(&mut wall, 100);
+    let mut trace_a = TraceBuffer::new();
+    let mut a = Curve::new(C, (100.0, 101.0), 1, 0.0, &mut trace_a);
+    let fast = FastPlayerBuffSprite::new(Coord::new(0, 0));
+    for _ in 0..3 {
+        fast.apply_player(&mut a, true);
+    }
+    let mut hit = false;
+    for _ in 0..30 {
+        a.act(PlayerInput::None);
+        hit |= a.collides_with(&wall);
+    }
+    assert!(hit);
+    assert!(a.pos().x > 150.0);
+}
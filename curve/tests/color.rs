@@ -0,0 +1,68 @@
+use curve::color::GameColor;
+
+fn c(value: u32) -> GameColor {
+    GameColor{value}
+}
+
+#[test]
+fn channels() {
+    let color = GameColor::from_rgb(0x12, 0x34, 0x56);
+    assert_eq!(color, c(0x12_3456));
+    assert_eq!((color.r(), color.g(), color.b()), (0x12, 0x34, 0x56));
+}
+
+#[test]
+fn rgb565_round_trip() {
+    assert_eq!(c(0xFF_FFFF).to_rgb565(), 0xFFFF);
+    assert_eq!(c(0xFF_0000).to_rgb565(), 0xF800);
+    assert_eq!(c(0x00_FF00).to_rgb565(), 0x07E0);
+    assert_eq!(c(0x00_00FF).to_rgb565(), 0x001F);
+    assert_eq!(GameColor::from_rgb565(0xFFFF), c(0xFF_FFFF));
+    assert_eq!(GameColor::from_rgb565(0x0000), c(0x00_0000));
+    // every 16 bit color survives the way through 24 bits
+    for value in (0..=0xFFFF_u32).step_by(97) {
+        assert_eq!(GameColor::from_rgb565(value as u16).to_rgb565(), value as u16);
+    }
+}
+
+#[test]
+fn argb8888() {
+    assert_eq!(c(0x12_3456).to_argb8888(0xFF), 0xFF12_3456);
+    assert_eq!(c(0x12_3456).to_argb8888(0x00), 0x0012_3456);
+    assert_eq!(GameColor::from_argb8888(0x8012_3456), c(0x12_3456));
+}
+
+#[test]
+fn hsv() {
+    assert_eq!(GameColor::from_hsv(0.0, 1.0, 1.0), c(0xFF_0000));
+    assert_eq!(GameColor::from_hsv(120.0, 1.0, 1.0), c(0x00_FF00));
+    assert_eq!(GameColor::from_hsv(240.0, 1.0, 1.0), c(0x00_00FF));
+    assert_eq!(GameColor::from_hsv(60.0, 1.0, 1.0), c(0xFF_FF00));
+    assert_eq!(GameColor::from_hsv(-120.0, 1.0, 1.0), c(0x00_00FF));
+    assert_eq!(GameColor::from_hsv(480.0, 1.0, 1.0), c(0x00_FF00));
+    assert_eq!(GameColor::from_hsv(42.0, 0.0, 1.0), c(0xFF_FFFF));
+    assert_eq!(c(0x00_0000).to_hsv(), (0.0, 0.0, 0.0));
+    assert_eq!(c(0xFF_FF00).to_hsv(), (60.0, 1.0, 1.0));
+    for value in [0x12_3456, 0xE6_9F00, 0x56_B4E9, 0xCC_79A7, 0x80_8080].iter() {
+        let (h, s, v) = c(*value).to_hsv();
+        assert_eq!(GameColor::from_hsv(h, s, v), c(*value));
+    }
+}
+
+#[test]
+fn lerp() {
+    let (black, white) = (c(0x00_0000), c(0xFF_FFFF));
+    assert_eq!(black.lerp(white, 0.0), black);
+    assert_eq!(black.lerp(white, 1.0), white);
+    assert_eq!(black.lerp(white, 0.5), c(0x80_8080));
+    assert_eq!(c(0xFF_0000).lerp(c(0x00_00FF), 0.25), c(0xBF_0040));
+    assert_eq!(black.lerp(white, 2.0), white);
+}
+
+#[test]
+fn blend_over() {
+    let (red, blue) = (c(0xFF_0000), c(0x00_00FF));
+    assert_eq!(red.blend_over(blue, 255), red);
+    assert_eq!(red.blend_over(blue, 0), blue);
+    assert_eq!(red.blend_over(blue, 128), c(0x80_007F));
+}
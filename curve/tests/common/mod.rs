@@ -0,0 +1,43 @@
+// every test crate includes this module, but not all of them use everything in it
+#![allow(dead_code)]
+
+use curve::color::GameColor;
+use curve::game::Player;
+use curve::geometry::AABBox;
+use curve::random::XorShift;
+use curve::trace::TracePool;
+use embedded_graphics::{Drawing, coord::Coord, drawable::Pixel};
+
+pub const COLORS: [GameColor; 4] = [
+    GameColor{value: 0x00_00FF},
+    GameColor{value: 0x00_FF00},
+    GameColor{value: 0xFF_0000},
+    GameColor{value: 0xFF_FF00},
+];
+
+/// Players spread over the field, the input regions don't matter here.
+pub fn players(traces: &mut TracePool, n: usize) -> Vec<Player> {
+    let mut rng = XorShift::new(42);
+    let region = AABBox::new(Coord::new(0, 0), Coord::new(10, 10));
+    let mut players: Vec<Player> = COLORS[..n].iter()
+                                              .zip(traces.buffers())
+                                              .map(|(c, trace)| {
+                                                  Player::new(*c, 3, &mut rng, region, region,
+                                                              trace)
+                                              })
+                                              .collect();
+    for (i, p) in players.iter_mut().enumerate() {
+        p.reset((60.0 + 100.0 * i as f32, 130.0), 0.0);
+    }
+    players
+}
+
+/// Throws away everything drawn, for running a game without a screen.
+pub struct NoDisplay;
+
+impl Drawing<GameColor> for NoDisplay {
+    fn draw<T>(&mut self, _: T)
+    where
+        T: Iterator<Item = Pixel<GameColor>>
+    {}
+}
@@ -0,0 +1,92 @@
+use embedded_graphics::coord::Coord;
+
+use curve::{HEIGHT, WIDTH};
+use curve::editor::{Editor, Tool, HIT_RADIUS};
+use curve::geometry::Vector2D;
+use curve::map::{Map, Obstacle};
+
+fn editor() -> Editor {
+    Editor::new(Map::default(), WIDTH as i32, HEIGHT as i32)
+}
+
+fn tap(editor: &mut Editor, x: i32, y: i32) -> bool {
+    let save = editor.touch(Some(Coord::new(x, y)));
+    editor.touch(None);
+    save
+}
+
+fn line(from: (f32, f32), to: (f32, f32)) -> Obstacle {
+    Obstacle::Polyline(vec![Vector2D{x: from.0, y: from.1}, Vector2D{x: to.0, y: to.1}])
+}
+
+#[test]
+fn points_snap_to_the_grid_inside_the_field() {
+    let editor = editor();
+    assert_eq!(editor.snap(Coord::new(14, 16)), Coord::new(10, 20));
+    assert_eq!(editor.snap(Coord::new(-7, 3)), Coord::new(0, 0));
+    // the toolbar is not part of the field
+    let corner = editor.field().bottom_right;
+    assert_eq!(editor.snap(Coord::new(478, 260)), corner);
+}
+
+#[test]
+fn dragging_draws_snapped_shapes() {
+    let mut editor = editor();
+    editor.touch(Some(Coord::new(12, 19)));
+    editor.touch(Some(Coord::new(58, 21)));
+    editor.touch(None);
+    assert_eq!(editor.map.obstacles, vec![line((10.0, 20.0), (60.0, 20.0))]);
+
+    editor.tool = Tool::Rect;
+    editor.touch(Some(Coord::new(101, 99)));
+    editor.touch(Some(Coord::new(148, 131)));
+    editor.touch(None);
+    assert_eq!(editor.map.obstacles.len(), 2);
+    match &editor.map.obstacles[1] {
+        Obstacle::Rect(r) => {
+            assert_eq!((r.top_left, r.bottom_right), (Coord::new(100, 100), Coord::new(150, 130)));
+        },
+        o => panic!("expected a rect, got {:?}", o),
+    }
+    // a tap is not a shape
+    assert!(!tap(&mut editor, 200, 200));
+    assert_eq!(editor.map.obstacles.len(), 2);
+}
+
+#[test]
+fn erase_hits_the_closest_obstacle_within_reach() {
+    let mut editor = editor();
+    editor.map.obstacles.push(line((10.0, 20.0), (60.0, 20.0)));
+    editor.map.obstacles.push(line((10.0, 30.0), (60.0, 30.0)));
+    let reach = HIT_RADIUS as i32;
+    assert_eq!(editor.obstacle_at(Coord::new(30, 20 - reach)), Some(0));
+    assert_eq!(editor.obstacle_at(Coord::new(30, 20 - reach - 1)), None);
+    assert_eq!(editor.obstacle_at(Coord::new(30, 24)), Some(0));
+    assert_eq!(editor.obstacle_at(Coord::new(30, 26)), Some(1));
+    assert_eq!(editor.obstacle_at(Coord::new(100, 20)), None);
+}
+
+#[test]
+fn toolbar_selects_tools_and_saves() {
+    let mut editor = editor();
+    editor.map.obstacles.push(line((10.0, 20.0), (60.0, 20.0)));
+    let toolbar = HEIGHT as i32 - 1;
+    let button = |i: i32| i * WIDTH as i32 / 5 + 10;
+
+    assert!(!tap(&mut editor, button(3), toolbar));
+    assert_eq!(editor.tool, Tool::Spawn);
+    tap(&mut editor, 200, 100);
+    assert_eq!(editor.map.spawns.len(), 1);
+
+    assert!(!tap(&mut editor, button(2), toolbar));
+    assert_eq!(editor.tool, Tool::Erase);
+    // a miss erases nothing
+    tap(&mut editor, 100, 60);
+    assert_eq!(editor.map.obstacles.len(), 1);
+    tap(&mut editor, 30, 22);
+    assert!(editor.map.obstacles.is_empty());
+    tap(&mut editor, 205, 95);
+    assert!(editor.map.spawns.is_empty());
+
+    assert!(tap(&mut editor, button(4), toolbar));
+}
@@ -0,0 +1,67 @@
+use curve::color::GameColor;
+use curve::effects::{Effects, RING_TICKS};
+use curve::geometry::Vector2D;
+use curve::player::{FIELD_LEFT, FIELD_TOP};
+use embedded_graphics::{Drawing, drawable::Pixel};
+
+/// Counts the drawn pixels.
+struct Counter(usize);
+
+impl Drawing<GameColor> for Counter {
+    fn draw<T>(&mut self, item_pixels: T)
+    where
+        T: Iterator<Item = Pixel<GameColor>>
+    {
+        self.0 += item_pixels.count();
+    }
+}
+
+#[test]
+fn ring_grows_and_leaves_a_marker() {
+    let mut effects = Effects::new();
+    let mut display = Counter(0);
+    effects.crash(Vector2D{x: 100.0, y: 100.0}, GameColor{value: 0xFF_0000});
+    let start = effects.rings[0].radius();
+    effects.rings[0].draw(&mut display, GameColor::default());
+    assert!(display.0 > 0);
+
+    effects.advance(RING_TICKS / 2);
+    assert!(effects.rings[0].radius() > start);
+    effects.advance(RING_TICKS);
+    // the last frame is still on the screen
+    effects.remove_done();
+    assert_eq!(effects.rings.len(), 1);
+    assert!(effects.rings[0].take_drawn().is_some());
+    effects.remove_done();
+    assert!(effects.rings.is_empty());
+    assert_eq!(effects.markers.len(), 1);
+}
+
+/// Remembers the drawn pixels.
+struct Pixels(Vec<(u32, u32)>);
+
+impl Drawing<GameColor> for Pixels {
+    fn draw<T>(&mut self, item_pixels: T)
+    where
+        T: Iterator<Item = Pixel<GameColor>>
+    {
+        self.0.extend(item_pixels.map(|p| (p.0[0], p.0[1])));
+    }
+}
+
+#[test]
+fn ring_at_the_edge_stays_off_the_hud() {
+    let mut effects = Effects::new();
+    effects.crash(Vector2D{x: FIELD_LEFT + 2.0, y: FIELD_TOP + 2.0}, GameColor{value: 0xFF_0000});
+    effects.advance(RING_TICKS / 2);
+    let mut display = Pixels(Vec::new());
+    effects.rings[0].draw(&mut display, GameColor::default());
+    effects.markers[0].draw(&mut display);
+    assert!(!display.0.is_empty());
+    for (x, y) in display.0 {
+        assert!(x as f32 >= FIELD_LEFT && y as f32 >= FIELD_TOP, "({}, {})", x, y);
+    }
+    // the repaint doesn't reach into the padding either
+    let drawn = effects.rings[0].take_drawn().unwrap();
+    assert_eq!((drawn.top_left[0], drawn.top_left[1]), (FIELD_LEFT as i32, FIELD_TOP as i32));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use curve::buffs::{Buff, BuffSprite, SlowBuffSprite};
+use curve::player::{Collide, Curve, Handicap, PlayerInput};
+use curve::trace::{TraceBuffer, TracePool};
+use embedded_graphics::coord::Coord;
+
+#[test]
+fn speed_handicap_stacks_with_buffs() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 1);
+    players[0].handicap = Handicap { speed: 0.5, ..Handicap::default() };
+    players[0].reset((100.0, 100.0), 0.0);
+    SlowBuffSprite::new(Coord::new(0, 0)).apply_players(&mut players, 0);
+    players[0].curve.act(PlayerInput::None);
+    assert!((players[0].curve.pos().x - 100.25).abs() < 0.01);
+}
+
+#[test]
+fn turn_and_radius_handicaps() {
+    let color = common::COLORS[0];
+    let handicap = Handicap { turn: 2.0, radius: 2.0, ..Handicap::default() };
+    let mut trace_curve = TraceBuffer::new();
+    let mut curve = Curve::with_handicap(color, (100.0, 100.0), 3, 0.0, handicap, &mut trace_curve);
+    curve.act(PlayerInput::Left);
+    assert!((curve.direction().y + 10_f32.to_radians().sin()).abs() < 0.001);
+    assert_eq!(curve.effective_radius(), 6.0);
+    assert_eq!(curve.trace().last().unwrap().radius, 6.0);
+}
+
+#[test]
+fn pickup_bonus_reaches_further() {
+    let color = common::COLORS[0];
+    let buff = BuffSprite::Slow(SlowBuffSprite::new(Coord::new(101, 122)));
+    let mut trace_plain = TraceBuffer::new();
+    let mut plain = Curve::new(color, (100.0, 100.0), 3, 0.0, &mut trace_plain);
+    let bonus = Handicap { pickup: 10.0, ..Handicap::default() };
+    let mut trace_magnet = TraceBuffer::new();
+    let mut magnet = Curve::with_handicap(color, (100.0, 100.0), 3, 0.0, bonus, &mut trace_magnet);
+    plain.act(PlayerInput::None);
+    magnet.act(PlayerInput::None);
+    assert!(!plain.collides_with(&buff));
+    assert!(magnet.collides_with(&buff));
+}
+
+#[test]
+fn only_active_handicaps_are_described() {
+    assert!(Handicap::default().is_none());
+    assert_eq!(Handicap::default().describe().as_str(), "");
+    let handicap = Handicap { speed: 0.8, pickup: 10.0, ..Handicap::default() };
+    assert!(!handicap.is_none());
+    assert_eq!(handicap.describe().as_str(), "speed 80%, pickup +10");
+}
@@ -0,0 +1,47 @@
+use curve::map::{EdgeBehavior, Map, ParseError, ParseErrorKind, MAPS};
+
+fn error(src: &str) -> ParseError {
+    Map::parse(src).expect_err("the map should not parse")
+}
+
+#[test]
+fn builtin_maps_survive_a_round_trip() {
+    for i in 0..MAPS.len() {
+        let map = Map::builtin(i);
+        assert_eq!(Map::parse(&map.to_text()), Ok(map));
+    }
+}
+
+#[test]
+fn edges_take_one_two_or_four_words() {
+    let map = Map::parse("edges wrap wall").unwrap();
+    assert_eq!((map.edges.left, map.edges.right), (EdgeBehavior::Wrap, EdgeBehavior::Wrap));
+    assert_eq!((map.edges.top, map.edges.bottom), (EdgeBehavior::Wall, EdgeBehavior::Wall));
+    let map = Map::parse("edges bounce wall wrap wrap").unwrap();
+    assert_eq!(Map::parse(&map.to_text()), Ok(map));
+}
+
+#[test]
+fn every_error_kind_is_reported_with_its_line() {
+    let cases = [
+        ("jump 1 2", ParseErrorKind::UnknownCommand),
+        ("name", ParseErrorKind::MissingValue),
+        ("rect 1 2 3", ParseErrorKind::MissingValue),
+        ("line 1 2 3", ParseErrorKind::MissingValue),
+        ("edges wrap wall bounce", ParseErrorKind::MissingValue),
+        ("circle 1 2 3 4", ParseErrorKind::TooManyValues),
+        ("edges wall wall wall wall wall", ParseErrorKind::TooManyValues),
+        ("rect 1 2 x 4", ParseErrorKind::InvalidNumber),
+        ("line 1 2 3 y", ParseErrorKind::InvalidNumber),
+        ("rect 10 10 5 20", ParseErrorKind::InvalidShape),
+        ("circle 1 2 0", ParseErrorKind::InvalidShape),
+        ("line 1 2", ParseErrorKind::InvalidShape),
+        ("edges wrap sticky", ParseErrorKind::InvalidEdge),
+        ("edges wrap wall wall wall", ParseErrorKind::OneSidedWrap),
+        ("edges wall bounce wall wrap", ParseErrorKind::OneSidedWrap),
+    ];
+    for (line, kind) in cases.iter() {
+        let src = format!("# comment\nname Broken\n\n{}\n", line);
+        assert_eq!(error(&src), ParseError { line: 4, kind: *kind }, "{}", line);
+    }
+}
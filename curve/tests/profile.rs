@@ -0,0 +1,122 @@
+use embedded_graphics::coord::Coord;
+
+use curve::{HEIGHT, WIDTH};
+use curve::keyboard::{Keyboard, Seat, KEY_HEIGHT, NAME_LEN};
+use curve::profile::{Profile, Profiles, MAX_PROFILES};
+use curve::storage::{MemStorage, RecordStore};
+
+const SEATS: [Seat; 4] = [Seat::Right, Seat::Left, Seat::Top, Seat::Bottom];
+
+/// Screen position of the middle of key `col` in `row` (3 is the row of delete and done).
+fn key(seat: Seat, row: i32, col: i32) -> Coord {
+    let (w, h) = seat.size();
+    let x = if row < 3 { col * w / 10 + w / 20 } else { col * w / 2 + w / 4 };
+    seat.to_screen(Coord::new(x, h - 4 * KEY_HEIGHT + row * KEY_HEIGHT + KEY_HEIGHT / 2))
+}
+
+fn tap(keyboard: &mut Keyboard, p: Coord) -> bool {
+    let done = keyboard.touch(Some(p));
+    keyboard.touch(None);
+    done
+}
+
+#[test]
+fn seats_map_the_whole_screen() {
+    for seat in SEATS.iter() {
+        let (w, h) = seat.size();
+        for p in [Coord::new(0, 0), Coord::new(w - 1, h - 1), Coord::new(17, 100)].iter() {
+            let s = seat.to_screen(*p);
+            assert!(s[0] >= 0 && s[0] < WIDTH as i32 && s[1] >= 0 && s[1] < HEIGHT as i32);
+            assert_eq!(seat.to_local(s), *p);
+        }
+    }
+    // the bottom of each view is at the edge of its seat
+    let near = |seat: Seat| seat.to_screen(Coord::new(seat.size().0 / 2, seat.size().1 - 1));
+    assert_eq!(near(Seat::Right)[0], WIDTH as i32 - 1);
+    assert_eq!(near(Seat::Left)[0], 0);
+    assert_eq!(near(Seat::Top)[1], 0);
+    assert_eq!(near(Seat::Bottom)[1], HEIGHT as i32 - 1);
+}
+
+#[test]
+fn names_are_typed_from_every_seat() {
+    for seat in SEATS.iter() {
+        let mut keyboard = Keyboard::new(*seat, Vec::new());
+        // "CAB", a typo and its correction
+        for (row, col) in [(0, 2), (0, 0), (1, 0)].iter() {
+            assert!(!tap(&mut keyboard, key(*seat, *row, *col)));
+        }
+        assert!(!tap(&mut keyboard, key(*seat, 3, 0)));
+        assert!(!tap(&mut keyboard, key(*seat, 0, 1)));
+        assert!(tap(&mut keyboard, key(*seat, 3, 1)));
+        assert_eq!(keyboard.text, "CAB");
+    }
+}
+
+#[test]
+fn keyboard_needs_a_name_and_limits_it() {
+    let mut keyboard = Keyboard::new(Seat::Bottom, Vec::new());
+    assert!(!tap(&mut keyboard, key(Seat::Bottom, 3, 1)));
+    // holding a key doesn't repeat it
+    keyboard.touch(Some(key(Seat::Bottom, 2, 0)));
+    keyboard.touch(Some(key(Seat::Bottom, 2, 0)));
+    keyboard.touch(None);
+    assert_eq!(keyboard.text, "U");
+    for _ in 0..NAME_LEN + 3 {
+        tap(&mut keyboard, key(Seat::Bottom, 2, 1));
+    }
+    assert_eq!(keyboard.text.len(), NAME_LEN);
+}
+
+#[test]
+fn taken_names_are_rejected() {
+    let mut keyboard = Keyboard::new(Seat::Top, vec![String::from("AB")]);
+    tap(&mut keyboard, key(Seat::Top, 0, 0));
+    tap(&mut keyboard, key(Seat::Top, 0, 1));
+    assert!(keyboard.is_taken());
+    assert!(!tap(&mut keyboard, key(Seat::Top, 3, 1)));
+    tap(&mut keyboard, key(Seat::Top, 0, 2));
+    assert!(tap(&mut keyboard, key(Seat::Top, 3, 1)));
+    assert_eq!(keyboard.text, "ABC");
+}
+
+#[test]
+fn profiles_survive_a_restart() {
+    let mut profiles = Profiles::default();
+    profiles.add(Profile::new("ANNA", 2, 0));
+    let bob = profiles.add(Profile::new("BOB", 1, 3));
+    profiles.profiles[bob].wins = 3;
+    profiles.profiles[bob].rounds = 7;
+
+    let mut store = RecordStore::open(MemStorage::new(512, 2));
+    store.save(&profiles).unwrap();
+    let mut store = RecordStore::open(store.into_inner());
+    assert_eq!(store.load::<Profiles>(), Some(profiles));
+}
+
+#[test]
+fn profiles_prefer_their_seat() {
+    let mut profiles = Profiles::default();
+    for (i, name) in ["A", "B", "C"].iter().enumerate() {
+        profiles.add(Profile::new(name, 0, i as u8));
+    }
+    assert_eq!(profiles.for_seat(2), vec![2, 0, 1]);
+    // same name replaces
+    profiles.add(Profile::new("B", 3, 3));
+    assert_eq!(profiles.profiles.len(), 3);
+    assert_eq!(profiles.profiles[1].color, 3);
+}
+
+#[test]
+fn the_least_played_profile_makes_room() {
+    let mut profiles = Profiles::default();
+    for i in 0..MAX_PROFILES {
+        let p = profiles.add(Profile::new(&format!("P{}", i), 0, 0));
+        profiles.profiles[p].rounds = 10 + i as u32;
+    }
+    profiles.profiles[3].rounds = 1;
+    profiles.add(Profile::new("NEW", 0, 0));
+    assert_eq!(profiles.profiles.len(), MAX_PROFILES);
+    assert_eq!(profiles.find("P3"), None);
+    assert!(profiles.find("NEW").is_some());
+}
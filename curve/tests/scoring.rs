@@ -0,0 +1,144 @@
+mod common;
+
+use common::NoDisplay;
+use curve::game::{Game, GameState, Player, DeathCause};
+use curve::map::Map;
+use curve::random::XorShift;
+use curve::scoring::{
+    Scoring, ScoringRule, SurvivalScoring, KillScoring, PlacementScoring, TimeScoring,
+    scoring_rule, SCORING_RULES,
+};
+use curve::settings::Settings;
+use curve::trace::TracePool;
+
+fn lose(rule: &ScoringRule, players: &mut [Player], loser: usize, cause: DeathCause) {
+    players[loser].lost = true;
+    rule.player_lost(players, loser, cause);
+}
+
+fn scores(players: &[Player]) -> Vec<i32> {
+    players.iter().map(|p| p.score).collect()
+}
+
+#[test]
+fn survival_rewards_everyone_still_alive() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 4);
+    lose(&SurvivalScoring, &mut players, 2, DeathCause::Border);
+    lose(&SurvivalScoring, &mut players, 0, DeathCause::Suicide);
+    assert_eq!(scores(&players), vec![1, 2, 0, 2]);
+}
+
+#[test]
+fn kills_go_to_the_owner_of_the_trace() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 3);
+    lose(&KillScoring, &mut players, 0, DeathCause::CutOffBy(1));
+    lose(&KillScoring, &mut players, 2, DeathCause::Suicide);
+    assert_eq!(scores(&players), vec![0, 1, -1]);
+}
+
+#[test]
+fn placement_follows_the_order_of_elimination() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 4);
+    lose(&PlacementScoring, &mut players, 3, DeathCause::Wall);
+    lose(&PlacementScoring, &mut players, 1, DeathCause::Border);
+    lose(&PlacementScoring, &mut players, 0, DeathCause::HeadOn(2));
+    PlacementScoring.round_finished(&mut players);
+    assert_eq!(scores(&players), vec![2, 1, 3, 0]);
+}
+
+#[test]
+fn the_last_place_gets_nothing_with_fewer_players() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 2);
+    lose(&PlacementScoring, &mut players, 0, DeathCause::Wall);
+    PlacementScoring.round_finished(&mut players);
+    assert_eq!(scores(&players), vec![0, 1]);
+
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 3);
+    lose(&PlacementScoring, &mut players, 1, DeathCause::Wall);
+    lose(&PlacementScoring, &mut players, 2, DeathCause::Suicide);
+    PlacementScoring.round_finished(&mut players);
+    assert_eq!(scores(&players), vec![2, 0, 1]);
+}
+
+#[test]
+fn a_head_on_shares_the_higher_place() {
+    let mut rng = XorShift::new(1);
+    let mut traces = TracePool::new();
+    let settings = Settings { buffs_enabled: false, slow_motion: false, ..Settings::default() };
+    let mut game = Game::new(&common::COLORS[..3], settings, Scoring::Placement, Map::default(),
+                             &mut rng, &mut traces);
+    game.new_game(&mut rng);
+    game.players[0].reset((100.0, 100.0), 0.0);
+    game.players[1].reset((130.0, 100.0), 180.0);
+    game.players[2].reset((300.0, 200.0), 0.0);
+    let mut steps = 0;
+    while let GameState::Playing = game.step(&mut rng, &mut NoDisplay, &[], 4) {
+        steps += 1;
+        assert!(steps < 100, "the round should be over");
+    }
+    assert_eq!(game.players[0].death, Some(DeathCause::HeadOn(1)));
+    // both crashed in the same step and get the second place
+    assert_eq!(scores(&game.players), vec![1, 1, 2]);
+}
+
+#[test]
+fn time_counts_seconds_alive() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 2);
+    players[0].alive_ticks = 250;
+    players[1].alive_ticks = 1099;
+    TimeScoring.round_finished(&mut players);
+    assert_eq!(scores(&players), vec![2, 10]);
+}
+
+#[test]
+fn every_rule_has_a_name() {
+    for i in 0..SCORING_RULES {
+        assert!(!scoring_rule(i).name().is_empty());
+    }
+}
+
+#[test]
+fn placements_rank_by_survival_time() {
+    let mut rng = XorShift::new(1);
+    let mut traces = TracePool::new();
+    let mut game = Game::new(&common::COLORS, Settings::default(), scoring_rule(0), Map::default(),
+                             &mut rng, &mut traces);
+    game.new_game(&mut rng);
+    for (p, (lost, ticks)) in game.players.iter_mut()
+                                          .zip([(true, 50), (false, 90), (true, 20), (true, 50)]
+                                               .iter()) {
+        p.lost = *lost;
+        p.alive_ticks = *ticks;
+    }
+    let placements: Vec<(usize, usize)> = game.placements().iter().cloned().collect();
+    assert_eq!(placements, vec![(1, 1), (0, 2), (3, 2), (2, 4)]);
+}
+
+#[test]
+fn winner_message_names_the_winning_player_or_team() {
+    let mut rng = XorShift::new(1);
+    let mut traces = TracePool::new();
+    let mut game = Game::new(&common::COLORS[..3], Settings::default(), scoring_rule(0),
+                             Map::default(), &mut rng, &mut traces);
+    game.players[0].lost = true;
+    game.players[2].lost = true;
+    assert_eq!(game.winner_message().as_str(), "Player B has won!");
+    game.players[1].lost = true;
+    assert_eq!(game.winner_message().as_str(), "Nobody has won!");
+
+    let teams = Settings { teams: true, ..Settings::default() };
+    // the first game still borrows its pool
+    let mut team_traces = TracePool::new();
+    let mut game = Game::new(&common::COLORS, teams, scoring_rule(0), Map::default(), &mut rng,
+                             &mut team_traces);
+    game.players[0].lost = true;
+    game.players[1].lost = true;
+    game.players[2].lost = true;
+    assert_eq!(game.winner_message().as_str(), "Team CD has won!");
+}
@@ -0,0 +1,40 @@
+use curve::border::Border;
+use curve::geometry::Vector2D;
+use curve::map::Map;
+use curve::player::{FIELD_LEFT, FIELD_RIGHT, FIELD_TOP, FIELD_BOTTOM};
+use curve::random::XorShift;
+use curve::spawn::{plan_spawns, MIN_EDGE_DISTANCE, MIN_PLAYER_DISTANCE};
+
+#[test]
+fn random_spawns_keep_their_distance() {
+    for seed in 1..10 {
+        let mut rng = XorShift::new(seed);
+        let spawns = plan_spawns(&mut rng, &Map::default(), &Border::new(), 4, false);
+        assert_eq!(spawns.len(), 4);
+        for (i, a) in spawns.iter().enumerate() {
+            assert!(a.pos.x >= FIELD_LEFT + MIN_EDGE_DISTANCE);
+            assert!(a.pos.x <= FIELD_RIGHT - MIN_EDGE_DISTANCE);
+            assert!(a.pos.y >= FIELD_TOP + MIN_EDGE_DISTANCE);
+            assert!(a.pos.y <= FIELD_BOTTOM - MIN_EDGE_DISTANCE);
+            for b in &spawns[i + 1..] {
+                assert!(a.pos.distance(b.pos) >= MIN_PLAYER_DISTANCE, "seed {}", seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn symmetric_spawns_are_evenly_spread() {
+    let mut rng = XorShift::new(7);
+    let spawns = plan_spawns(&mut rng, &Map::default(), &Border::new(), 3, true);
+    let center = Vector2D{x: (FIELD_LEFT + FIELD_RIGHT) / 2.0, y: (FIELD_TOP + FIELD_BOTTOM) / 2.0};
+    let radius = spawns[0].pos.distance(center);
+    let side = spawns[0].pos.distance(spawns[1].pos);
+    for (i, s) in spawns.iter().enumerate() {
+        assert!((s.pos.distance(center) - radius).abs() < 0.01);
+        let next = spawns[(i + 1) % spawns.len()].pos;
+        assert!((s.pos.distance(next) - side).abs() < 0.01);
+        // all circle the same way, tangential to the circle
+        assert!(s.direction().dot(s.pos - center).abs() < 0.01);
+    }
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::NoDisplay;
+use curve::game::{Game, GameState};
+use curve::map::Map;
+use curve::player::PlayerInput;
+use curve::random::XorShift;
+use curve::scoring::scoring_rule;
+use curve::settings::Settings;
+use curve::trace::TracePool;
+
+#[test]
+fn a_scripted_round_counts_kills_and_suicides() {
+    let mut rng = XorShift::new(1);
+    let mut traces = TracePool::new();
+    let settings = Settings { buffs_enabled: false, slow_motion: false, ..Settings::default() };
+    let mut game = Game::new(&common::COLORS[..3], settings, scoring_rule(1), Map::default(),
+                             &mut rng, &mut traces);
+    game.new_game(&mut rng);
+    // A goes down, B crosses its trace and C circles into its own
+    game.players[0].reset((200.0, 50.0), 90.0);
+    game.players[1].reset((100.0, 100.0), 0.0);
+    game.players[2].reset((350.0, 180.0), 0.0);
+    let inputs = [PlayerInput::None, PlayerInput::None, PlayerInput::Left];
+    let mut steps = 0;
+    while let GameState::Playing = game.step(&mut rng, &mut NoDisplay, &inputs, 4) {
+        steps += 1;
+        assert!(steps < 300, "the round should be over");
+    }
+
+    let stats: Vec<_> = game.players.iter().map(|p| p.stats).collect();
+    assert_eq!((stats[0].kills, stats[0].deaths), (1, 0));
+    assert_eq!((stats[1].kills, stats[1].deaths, stats[1].suicides), (0, 1, 0));
+    assert_eq!((stats[2].kills, stats[2].deaths, stats[2].suicides), (0, 1, 1));
+    assert_eq!(game.players[2].stats.describe().as_str(), "kills 0, deaths 1, suicides 1");
+}
@@ -0,0 +1,125 @@
+use curve::map::CustomMaps;
+use curve::settings::{Settings, HighScores, Stats};
+use curve::storage::{MemStorage, Record, RecordStore};
+
+#[test]
+fn records_survive_a_restart() {
+    let mut store = RecordStore::open(MemStorage::new(512, 2));
+    let settings = Settings { curve_radius: 5, teams: true, ..Settings::default() };
+    store.save(&settings).unwrap();
+
+    let mut store = RecordStore::open(store.into_inner());
+    assert_eq!(store.load::<Settings>(), Some(settings));
+    assert_eq!(store.load::<HighScores>(), None);
+}
+
+#[test]
+fn an_interrupted_save_keeps_the_old_record() {
+    let mut store = RecordStore::open(MemStorage::new(512, 2));
+    let old = Settings { curve_radius: 4, ..Settings::default() };
+    store.save(&old).unwrap();
+
+    let mut storage = store.into_inner();
+    storage.cut_power_after(6);
+    let mut store = RecordStore::open(storage);
+    assert!(store.save(&Settings { curve_radius: 7, ..old }).is_err());
+
+    let mut storage = store.into_inner();
+    storage.restore_power();
+    let mut store = RecordStore::open(storage);
+    assert_eq!(store.load::<Settings>(), Some(old));
+}
+
+#[test]
+fn compaction_spreads_the_erases() {
+    let mut store = RecordStore::open(MemStorage::new(256, 3));
+    for i in 0..200 {
+        let settings = Settings { curve_radius: i % 7 + 1, ..Settings::default() };
+        store.save(&settings).unwrap();
+    }
+    let storage = store.into_inner();
+    let counts = storage.erase_counts();
+    let max = *counts.iter().max().unwrap();
+    let min = *counts.iter().min().unwrap();
+    assert!(min > 0);
+    assert!(max - min <= 2, "{:?}", counts);
+}
+
+#[test]
+fn high_scores_are_kept_by_name() {
+    let mut high_scores = HighScores::default();
+    assert_eq!(high_scores.update(10, "ANNA", 1), Some(0));
+    assert_eq!(high_scores.update(20, "BOB", 1), Some(0));
+    // a better score of the same session replaces the old entry
+    assert_eq!(high_scores.update(30, "ANNA", 1), Some(0));
+    assert_eq!(high_scores.entries[0].name, "ANNA");
+    assert_eq!(high_scores.entries[1].name, "BOB");
+    assert_eq!(high_scores.entries[2].score, 0);
+
+    let mut store = RecordStore::open(MemStorage::new(512, 2));
+    store.save(&high_scores).unwrap();
+    let mut store = RecordStore::open(store.into_inner());
+    assert_eq!(store.load::<HighScores>(), Some(high_scores));
+}
+
+#[test]
+fn old_high_scores_name_the_seat() {
+    // version 1: score, seat, session
+    let mut data = vec![12, 0, 0, 0, 2, 1, 0, 0, 0];
+    data.resize(5 * 9, 0);
+    let high_scores = HighScores::decode(1, &data).unwrap();
+    assert_eq!(high_scores.entries[0].score, 12);
+    assert_eq!(high_scores.entries[0].name, "Player C");
+    assert_eq!(high_scores.entries[0].session, 1);
+    assert_eq!(high_scores.entries[1].name, "");
+}
+
+#[test]
+fn old_settings_get_defaults_for_new_fields() {
+    // version 3 ended with the shrink step
+    let data = [5, 0x90, 0x01, 0, 200, 0, 1, 1, 1, 0xF4, 0x01, 0x2C, 0x01, 20];
+    let settings = Settings::decode(3, &data).unwrap();
+    let defaults = Settings::default();
+    assert_eq!(settings, Settings {
+        curve_radius: 5,
+        max_tt_buff: 400,
+        buffs_enabled: false,
+        ready_cooldown: 200,
+        teams: true,
+        friendly_trails_passable: true,
+        shrinking_border: true,
+        shrink_grace: 500,
+        shrink_interval: 300,
+        shrink_step: 20,
+        ..defaults
+    });
+}
+
+#[test]
+fn every_record_survives_compaction() {
+    let mut store = RecordStore::open(MemStorage::new(256, 3));
+    let mut high_scores = HighScores::default();
+    high_scores.update(42, "ANNA", 1);
+    store.save(&high_scores).unwrap();
+    let mut settings = Settings::default();
+    for i in 0..50 {
+        settings.curve_radius = i % 7 + 1;
+        store.save(&settings).unwrap();
+        assert_eq!(store.load::<HighScores>().as_ref(), Some(&high_scores));
+    }
+    assert_eq!(store.load::<Settings>(), Some(settings));
+
+    let mut store = RecordStore::open(store.into_inner());
+    assert_eq!(store.load::<Settings>(), Some(settings));
+    assert_eq!(store.load::<HighScores>(), Some(high_scores));
+}
+
+#[test]
+fn records_of_a_newer_firmware_are_ignored() {
+    let mut data = Vec::new();
+    Stats::default().encode(&mut data);
+    assert_eq!(Stats::decode(Stats::VERSION + 1, &data), None);
+    data.clear();
+    CustomMaps::default().encode(&mut data);
+    assert_eq!(CustomMaps::decode(CustomMaps::VERSION + 1, &data), None);
+}
@@ -0,0 +1,37 @@
+use curve::theme::{THEMES, next_player_color};
+
+#[test]
+fn players_can_be_told_apart() {
+    for theme in THEMES.iter() {
+        for (i, a) in theme.players.iter().enumerate() {
+            assert_ne!(*a, theme.background, "{}", theme.name);
+            for b in &theme.players[i + 1..] {
+                assert_ne!(a, b, "{}", theme.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn color_buff_cycles_through_the_palette() {
+    for theme in THEMES.iter() {
+        for teams in [false, true].iter() {
+            let palette = theme.player_colors(*teams);
+            let mut color = palette[0];
+            for i in 1..=palette.len() {
+                color = next_player_color(palette, color);
+                assert_eq!(color, palette[i % palette.len()], "{}", theme.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn team_games_disguise_curves_with_team_colors() {
+    // some team colors are player colors as well, the palette decides
+    for theme in THEMES.iter() {
+        let teams = theme.player_colors(true);
+        assert_eq!(next_player_color(teams, teams[0]), teams[1], "{}", theme.name);
+        assert_eq!(next_player_color(teams, teams[2]), teams[3], "{}", theme.name);
+    }
+}
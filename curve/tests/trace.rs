@@ -0,0 +1,105 @@
+mod common;
+
+use curve::buffs::{Buff, DrunkenBuffSprite};
+use curve::geometry::Vector2D;
+use curve::player::PlayerInput;
+use curve::trace::{
+    Segment, Trace, TraceBuffer, TracePool, SIMPLIFY_TOLERANCE, TRACE_CAPACITY,
+};
+use embedded_graphics::coord::Coord;
+
+fn distance_to(segments: &[Segment], p: Vector2D) -> f32 {
+    segments.iter()
+            .map(|s| p.distance_to_segment(s.start, s.end))
+            .fold(core::f32::MAX, f32::min)
+}
+
+/// Checks on a grid of points starting at `from` that both traces are about equally far away.
+fn assert_within_tolerance(trace: &[Segment], exact: &[Segment], from: Vector2D) {
+    for x in 0..100 {
+        for y in 0..60 {
+            let p = Vector2D{x: from.x + 3.3 * x as f32, y: from.y + 3.3 * y as f32};
+            let d_exact = distance_to(exact, p);
+            let d_trace = distance_to(trace, p);
+            assert!((d_exact - d_trace).abs() <= SIMPLIFY_TOLERANCE + 0.01,
+                    "{:?}: {} vs {}", p, d_exact, d_trace);
+        }
+    }
+}
+
+/// A wobbly path like a player steering left and right.
+fn path() -> Vec<Vector2D> {
+    let mut pos = Vector2D{x: 50.0, y: 130.0};
+    let mut direction = Vector2D{x: 1.0, y: 0.0};
+    let mut points = vec![pos];
+    for i in 0..400 {
+        let turn = if (i / 40) % 2 == 0 { 3.0 } else { -2.0 };
+        direction = direction.rotate(turn * core::f32::consts::PI / 180.0);
+        pos = pos + direction;
+        points.push(pos);
+    }
+    points
+}
+
+#[test]
+fn simplification_keeps_collisions_within_tolerance() {
+    let points = path();
+    let mut buffer = TraceBuffer::new();
+    let mut trace = Trace::new(points[0], 3.0, &mut buffer);
+    let mut exact = Vec::new();
+    for w in points.windows(2) {
+        trace.advance(w[1], 3.0);
+        exact.push(Segment{start: w[0], end: w[1], radius: 3.0});
+    }
+    assert!(trace.len() < exact.len() / 4, "{} segments", trace.len());
+    assert_within_tolerance(&trace, &exact, Vector2D{x: 40.0, y: 40.0});
+}
+
+#[test]
+fn a_curve_turned_by_a_buff_keeps_its_bends() {
+    let mut traces = TracePool::new();
+    let mut players = common::players(&mut traces, 2);
+    players[0].reset((240.0, 136.0), 0.0);
+    // the others get drunk
+    DrunkenBuffSprite::new(Coord::new(0, 0)).apply_players(&mut players, 1);
+    let mut exact = Vec::new();
+    for _ in 0..150 {
+        let from = players[0].curve.pos();
+        players[0].curve.act(PlayerInput::None);
+        exact.push(Segment{start: from, end: players[0].curve.pos(), radius: 3.0});
+    }
+    assert!(players[0].curve.trace().len() > 1);
+    assert_within_tolerance(players[0].curve.trace(), &exact, Vector2D{x: 80.0, y: 40.0});
+}
+
+#[test]
+fn a_new_radius_starts_a_new_segment() {
+    let mut buffer = TraceBuffer::new();
+    let mut trace = Trace::new(Vector2D{x: 0.0, y: 0.0}, 3.0, &mut buffer);
+    trace.advance(Vector2D{x: 10.0, y: 0.0}, 3.0);
+    trace.advance(Vector2D{x: 20.0, y: 0.0}, 4.5);
+    assert_eq!(trace.len(), 2);
+    assert_eq!(trace[1].radius, 4.5);
+    assert_eq!(trace[1].start, Vector2D{x: 10.0, y: 0.0});
+}
+
+#[test]
+fn a_full_trace_is_reported() {
+    let mut buffer = TraceBuffer::new();
+    let mut trace = Trace::new(Vector2D{x: 0.0, y: 0.0}, 3.0, &mut buffer);
+    let mut i = 0;
+    while !trace.is_full() {
+        // a zigzag can't be simplified
+        let y = if i % 2 == 0 { 10.0 } else { 0.0 };
+        trace.advance(Vector2D{x: i as f32 * 10.0, y}, 3.0);
+        i += 1;
+        assert!(i < 10_000);
+    }
+    assert_eq!(trace.len(), TRACE_CAPACITY);
+    trace.advance(Vector2D{x: -50.0, y: 0.0}, 3.0);
+    assert_eq!(trace.len(), TRACE_CAPACITY);
+    // the buffer is reused for the next round
+    trace.reset(Vector2D{x: 0.0, y: 0.0}, 3.0);
+    assert!(!trace.is_full());
+    assert_eq!(trace.len(), 1);
+}
@@ -0,0 +1,4 @@
+/* The settings are kept in the last two flash sectors, see `src/flash.rs`. Everything the
+   firmware puts into flash, including the initial values of `.data`, has to end before them. */
+ASSERT(__sidata + (__edata - __sdata) <= 0x08080000,
+       "the firmware reaches into the settings sectors at 0x0808_0000");
@@ -1,8 +1,8 @@
 use embedded_graphics::{
     Drawing,
     drawable::Pixel,
-    pixelcolor::PixelColor,
 };
+use curve::color::GameColor;
 
 use stm32f7_discovery::lcd::{Framebuffer, Layer, Color, WIDTH, HEIGHT};
 
@@ -37,18 +37,3 @@ impl <'a, F: Framebuffer> Drawing<GameColor> for LcdDisplay<'a, F> {
         }
     }
 }
-
-#[derive(Copy, Clone, PartialEq, Debug, Default)]
-pub struct GameColor {
-    pub value: u32,
-}
-
-impl PixelColor for GameColor {}
-
-impl From<u8> for GameColor {
-    fn from(other: u8) -> Self {
-        GameColor {
-            value: u32::from(other),
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,105 @@
+use core::ptr;
+use cortex_m::asm;
+use stm32f7::stm32f7x6::FLASH;
+
+use curve::storage::{Storage, StorageError};
+
+const KEY1: u32 = 0x4567_0123;
+const KEY2: u32 = 0xCDEF_89AB;
+
+// The last two 256 KiB sectors of the 1 MiB flash. The firmware has to stay below
+// `0x0808_0000`, `flash_limit.x` checks that when linking.
+const FIRST_SECTOR: u8 = 6;
+const SECTOR_BASE: usize = 0x0808_0000;
+const SECTOR_SIZE: usize = 256 * 1024;
+const SECTOR_COUNT: usize = 2;
+
+// OPERR | WRPERR | PGAERR | PGPERR | ERSERR
+const SR_ERRORS: u32 = 0b1111_0010;
+
+/// `Storage` on the internal flash of the STM32F746G.
+pub struct FlashStorage {
+    flash: FLASH,
+}
+
+impl FlashStorage {
+    pub fn new(flash: FLASH) -> Self {
+        Self { flash }
+    }
+
+    fn unlock(&mut self) {
+        if self.flash.cr.read().lock().bit_is_set() {
+            self.flash.keyr.write(|w| unsafe { w.key().bits(KEY1) });
+            self.flash.keyr.write(|w| unsafe { w.key().bits(KEY2) });
+        }
+    }
+
+    fn lock(&mut self) {
+        self.flash.cr.modify(|_, w| w.lock().set_bit());
+    }
+
+    fn wait_ready(&self) -> Result<(), StorageError> {
+        while self.flash.sr.read().bsy().bit_is_set() {}
+        let errors = self.flash.sr.read().bits() & SR_ERRORS;
+        if errors != 0 {
+            // the error flags are cleared by writing 1
+            self.flash.sr.write(|w| unsafe { w.bits(errors) });
+            return Err(StorageError::Hardware);
+        }
+        Ok(())
+    }
+
+    fn address(sector: usize, offset: usize) -> usize {
+        assert!(sector < SECTOR_COUNT, "Sector out of range");
+        SECTOR_BASE + sector * SECTOR_SIZE + offset
+    }
+}
+
+impl Storage for FlashStorage {
+    fn sector_size(&self) -> usize {
+        SECTOR_SIZE
+    }
+
+    fn sector_count(&self) -> usize {
+        SECTOR_COUNT
+    }
+
+    fn read(&self, sector: usize, offset: usize, buf: &mut [u8]) {
+        let addr = Self::address(sector, offset);
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = unsafe { ptr::read_volatile((addr + i) as *const u8) };
+        }
+    }
+
+    fn write(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+        let addr = Self::address(sector, offset);
+        self.wait_ready()?;
+        self.unlock();
+        // byte wise programming (PSIZE = x8) works for every supply voltage
+        self.flash.cr.modify(|_, w| unsafe { w.psize().bits(0b00) }.pg().set_bit());
+        let mut res = Ok(());
+        for (i, b) in data.iter().enumerate() {
+            unsafe { ptr::write_volatile((addr + i) as *mut u8, *b) };
+            asm::dsb();
+            res = self.wait_ready();
+            if res.is_err() { break; }
+        }
+        self.flash.cr.modify(|_, w| w.pg().clear_bit());
+        self.lock();
+        res
+    }
+
+    fn erase(&mut self, sector: usize) -> Result<(), StorageError> {
+        assert!(sector < SECTOR_COUNT, "Sector out of range");
+        self.wait_ready()?;
+        self.unlock();
+        self.flash.cr.modify(|_, w| unsafe {
+            w.psize().bits(0b00).snb().bits(FIRST_SECTOR + sector as u8)
+        }.ser().set_bit());
+        self.flash.cr.modify(|_, w| w.strt().set_bit());
+        let res = self.wait_ready();
+        self.flash.cr.modify(|_, w| w.ser().clear_bit());
+        self.lock();
+        res
+    }
+}
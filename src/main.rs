@@ -13,13 +13,10 @@ extern crate cortex_m_semihosting;
 extern crate stm32f7;
 #[macro_use]
 extern crate stm32f7_discovery;
+extern crate curve;
 
-pub mod buffs;
 pub mod display;
-pub mod geometry;
-pub mod player;
-pub mod game;
-pub mod border;
+pub mod flash;
 
 use stm32f7::stm32f7x6::I2C3;
 use stm32f7_discovery::i2c::I2C;
@@ -31,10 +28,13 @@ use embedded_graphics::{
     fonts::{
         Font6x8, Font12x16,
     },
+    primitives::Rect,
 };
 use alloc::vec::Vec;
+use alloc::string::String;
 use alloc_cortex_m::CortexMHeap;
 use core::alloc::Layout as AllocLayout;
+use core::fmt::Write;
 use core::panic::PanicInfo;
 use rt::{entry, exception};
 use stm32f7::stm32f7x6::{
@@ -44,7 +44,7 @@ use stm32f7::stm32f7x6::{
 use stm32f7_discovery::{
     gpio::{GpioPort, OutputPin},
     init,
-    lcd::{self, Color, HEIGHT, WIDTH},
+    lcd::{self, Color, Framebuffer, HEIGHT, WIDTH},
     random::Rng,
     system_clock::{self, Hz},
     touch,
@@ -52,17 +52,39 @@ use stm32f7_discovery::{
 use embedded_graphics::coord::Coord;
 
 
-use display::{GameColor, LcdDisplay};
-use game::{Game, GameState};
+use curve::color::GameColor;
+use curve::game::{Game, GameState, DeathCause, team_name, player_name};
+use curve::player::{Handicap, PlayerInput};
+use curve::settings::{Settings, HighScores, Stats, MAX_PLAYERS};
+use curve::scoring::{scoring_rule, ScoringRule, SCORING_RULES};
+use curve::map::{Map, MAPS, CustomMaps};
+use curve::editor::Editor;
+use curve::storage::{RecordStore, Storage};
+use curve::random::Random;
+use curve::bot::bot_inputs;
+use curve::theme::{Theme, THEMES, theme};
+use curve::keyboard::{Keyboard, Seat};
+use curve::profile::{Profile, Profiles};
+use curve::trace::TracePool;
+use display::LcdDisplay;
+use flash::FlashStorage;
+use heapless::consts::U64;
 
 const HEAP_SIZE: usize = 1024 * 1024; // in bytes
 
-const C_PLAYER_A: GameColor = GameColor{value: 0x00_00FF};
-const C_PLAYER_B: GameColor = GameColor{value: 0x00_FF00};
-const C_PLAYER_C: GameColor = GameColor{value: 0xFF_0000};
-const C_PLAYER_D: GameColor = GameColor{value: 0xFF_FF00};
-const C_BLACK: GameColor = GameColor{value: 0x00_0000};
-const C_WHITE: GameColor = GameColor{value: 0xFF_FFFF};
+/// Ticks without a touch on the title screen until the bots start a demo.
+const ATTRACT_AFTER: usize = 3000;
+/// Ticks the result of a demo round stays on screen.
+const DEMO_RESULT: usize = 200;
+/// Ticks the round summary waits for every player to confirm.
+const SUMMARY_TIMEOUT: usize = 1500;
+/// Steps of the speed, turn and radius handicaps, tapping a row cycles through them.
+const HANDICAP_FACTORS: [f32; 5] = [1.0, 1.2, 1.4, 0.6, 0.8];
+/// Steps of the extra buff pickup reach, in pixels.
+const PICKUP_BONUSES: [f32; 4] = [0.0, 5.0, 10.0, 15.0];
+
+/// The traces of the running game, too big for the stack. Only `main` touches it.
+static mut TRACES: TracePool = TracePool::new();
 
 pub fn to_coord(t: (i32, i32)) -> Coord {
     Coord::new(t.0, t.1)
@@ -137,35 +159,92 @@ fn main() -> ! {
     // controller might not be ready yet
     touch::check_family_id(&mut i2c_3).unwrap();
 
-    let mut rng = Rng::init(&mut rng, &mut rcc).expect("RNG init failed");
+    let mut rng = HardwareRng(Rng::init(&mut rng, &mut rcc).expect("RNG init failed"));
+
+    let mut store = RecordStore::open(FlashStorage::new(flash));
+    let mut settings: Settings = store.load().unwrap_or_default();
+    let mut high_scores: HighScores = store.load().unwrap_or_default();
+    let mut stats: Stats = store.load().unwrap_or_default();
+    let mut profiles: Profiles = store.load().unwrap_or_default();
+    stats.sessions += 1;
+    lcd.set_background_color(Color::from_hex(theme(settings.theme as usize).background.value));
 
     let mut display = LcdDisplay::new(&mut layer_1);
+    // safe, nothing else uses the static and `main` never returns
+    let traces = unsafe { &mut TRACES };
 
-    let num_player = player_select(&mut display, &mut i2c_3);
-    let player_c = &[C_PLAYER_A, C_PLAYER_B, C_PLAYER_C, C_PLAYER_D];
-    let mut game = Game::new(&player_c[..num_player], &mut rng);
+    let num_player = player_select(&mut display, &mut i2c_3, &mut rng, &settings, traces);
+    wait_release(&mut i2c_3);
+    display.clear();
+    let scoring = scoring_select(&mut display, &mut i2c_3, &settings);
+    wait_release(&mut i2c_3);
+    display.clear();
+    if num_player == 4 {
+        team_select(&mut display, &mut i2c_3, &mut settings);
+        wait_release(&mut i2c_3);
+        display.clear();
+    }
+    arena_select(&mut display, &mut i2c_3, &mut settings);
+    wait_release(&mut i2c_3);
+    display.clear();
+    spawn_select(&mut display, &mut i2c_3, &mut settings);
+    wait_release(&mut i2c_3);
+    display.clear();
+    theme_select(&mut display, &mut i2c_3, &mut settings);
+    let theme = *theme(settings.theme as usize);
+    lcd.set_background_color(Color::from_hex(theme.background.value));
+    wait_release(&mut i2c_3);
+    display.clear();
+    let chosen = profile_select(&mut display, &mut i2c_3, num_player, &mut profiles, &theme);
+    if store.save(&profiles).is_err() && cfg!(debug_assertions) {
+        println!("failed to save profiles");
+    }
+    let names: Vec<String> = chosen.iter()
+                                   .enumerate()
+                                   .map(|(i, c)| c.clone().unwrap_or_else(|| {
+                                       format!("Player {}", player_letter(i))
+                                   }))
+                                   .collect();
+    let handicaps = handicap_select(&mut display, &mut i2c_3, &names, &theme);
+    wait_release(&mut i2c_3);
+    display.clear();
+    let mut custom_maps: CustomMaps = store.load().unwrap_or_default();
+    let map = match map_select(&mut display, &mut i2c_3, &custom_maps, &theme) {
+        Some(map) => map,
+        None => {
+            wait_release(&mut i2c_3);
+            display.clear();
+            let map = edit_map(&mut display, &mut i2c_3, &custom_maps, &theme);
+            custom_maps.add(&map);
+            if store.save(&custom_maps).is_err() && cfg!(debug_assertions) {
+                println!("failed to save map");
+            }
+            map
+        },
+    };
+    if store.save(&settings).is_err() && cfg!(debug_assertions) {
+        println!("failed to save settings");
+    }
+    let player_c = profile_colors(&theme, num_player == 4 && settings.teams, &profiles, &chosen);
+    let mut game = Game::new(&player_c[..num_player], settings, scoring_rule(scoring), map,
+                             &mut rng, traces);
+    for ((p, name), handicap) in game.players.iter_mut().zip(names).zip(handicaps) {
+        p.name = player_name(&name);
+        p.handicap = handicap;
+    }
     display.clear();
 
     loop {
     
         game.new_game(&mut rng);
 
-        ready_screen(&mut display, 3*100);
+        ready_screen(&mut display, &game, settings.ready_cooldown as i32);
         display.clear();
+        game.draw_arena(&mut display);
 
-        for (i, p) in game.players.iter().enumerate() {
-            match i {
-                0 => draw_text_right(&mut display, &format!("<--- Player A: {:04}  --->", p.score),
-                                C_BLACK, C_PLAYER_A),
-                1 => draw_text_left(&mut display, &format!("<--- Player B: {:04}  --->", p.score),
-                                C_BLACK, C_PLAYER_B),
-                2 => draw_text_top(&mut display, &format!("<--- Player C: {:04}  --->", p.score),
-                                C_BLACK, C_PLAYER_C),
-                3 => draw_text_bottom(&mut display, &format!("<--- Player D: {:04}  --->", p.score),
-                                C_BLACK, C_PLAYER_D),
-                _ => {},
-            }
-        }
+        let mut drawn_scores = [0; MAX_PLAYERS];
+        draw_hud(&mut display, &game, &player_c, &mut drawn_scores);
+        let round_start = system_clock::ticks();
         let mut last_ticks = system_clock::ticks();
         let mut touches: Vec<Coord> = Vec::new();
         loop {
@@ -182,74 +261,467 @@ fn main() -> ! {
                     i32::from(touch.y),
                 ));
             }
-            match game.step(&mut rng, &mut display, &touches, d_ticks) {
+            let inputs = game.touch_inputs(&touches);
+            match game.step(&mut rng, &mut display, &inputs, d_ticks) {
                 GameState::Finished => {
-                    let mut msg = "Player ? has won!";
-                    for (i, p) in game.players.iter().enumerate() {
-                        if !p.lost {
-                            match i {
-                                0 => msg = "Player A has won!",
-                                1 => msg = "Player B has won!",
-                                2 => msg = "Player C has won!",
-                                3 => msg = "Player D has won!",
-                                _ => msg = "Player ? has won!",
-                            }
-                        }
-                    }
-                    text_above_mid(&mut display, &msg, C_BLACK, C_WHITE);
+                    record_round(&mut store, &game, &mut high_scores, &mut stats,
+                                 &mut profiles, &chosen, system_clock::ticks() - round_start);
+                    display.clear();
+                    round_summary(&mut display, &mut i2c_3, &game);
+                    wait_release(&mut i2c_3);
+                    display.clear();
                     break;
                 },
-                GameState::Playing => {},
+                GameState::Playing => {
+                    // survival, kill and placement points come in when someone crashes
+                    if game.players.iter().zip(&drawn_scores).any(|(p, s)| p.score != *s) {
+                        draw_hud(&mut display, &game, &player_c, &mut drawn_scores);
+                    }
+                },
             }
             touches.clear();
         }
     }
 }
 
-fn player_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>) -> usize
+fn record_round<S: Storage>(store: &mut RecordStore<S>, game: &Game,
+                            high_scores: &mut HighScores, stats: &mut Stats,
+                            profiles: &mut Profiles, chosen: &[Option<String>], ticks: usize) {
+    stats.rounds_played += 1;
+    stats.buffs_collected += game.buffs_collected;
+    stats.ticks_played = stats.ticks_played.wrapping_add(ticks as u32);
+    for i in 0..game.players.len() {
+        if game.is_winner(i) {
+            stats.wins[i] += 1;
+        }
+    }
+    let mut changed = false;
+    for p in game.players.iter() {
+        changed |= high_scores.update(p.score.max(0) as u32, &p.name, stats.sessions).is_some();
+    }
+    if store.save(stats).is_err() && cfg!(debug_assertions) {
+        println!("failed to save stats");
+    }
+    if changed && store.save(high_scores).is_err() && cfg!(debug_assertions) {
+        println!("failed to save high scores");
+    }
+    let mut played = false;
+    for (i, name) in chosen.iter().enumerate().take(game.players.len()) {
+        if let Some(p) = name.as_ref().and_then(|n| profiles.find(n)) {
+            profiles.profiles[p].rounds += 1;
+            if game.is_winner(i) {
+                profiles.profiles[p].wins += 1;
+            }
+            played = true;
+        }
+    }
+    if played && store.save(profiles).is_err() && cfg!(debug_assertions) {
+        println!("failed to save profiles");
+    }
+}
+
+/// Waits for the number of players, bots play demo rounds while nobody touches the screen.
+fn player_select<F: Framebuffer>(display: &mut LcdDisplay<F>, i2c_3: &mut I2C<I2C3>,
+                                 rng: &mut Random, settings: &Settings,
+                                 traces: &mut TracePool) -> usize {
+    loop {
+        draw_player_select(display, theme(settings.theme as usize));
+        let idle_since = system_clock::ticks();
+        while system_clock::ticks() - idle_since < ATTRACT_AFTER {
+            for touch in &touch::touches(i2c_3).unwrap() {
+                if (touch.x as usize) < WIDTH / 4 {
+                    return 1;
+                } else if (touch.x as usize) < WIDTH / 2 {
+                    return 2;
+                } else if (touch.x as usize) < 3* WIDTH / 4 {
+                    return 3;
+                } else {
+                    return 4;
+                } 
+            }
+        }
+        attract_mode(display, i2c_3, rng, settings, traces);
+        // the touch that ended the demo doesn't pick a number
+        wait_release(i2c_3);
+        display.clear();
+    }
+}
+
+fn draw_player_select<D>(display: &mut D, theme: &Theme)
 where 
     D: Drawing<GameColor>,
 {
     let w1_4 = (WIDTH/4) as i32;
     display.draw(Font12x16::render_str("1")
-            .with_stroke(Some(C_PLAYER_A))
-            .with_fill(Some(C_BLACK))
+            .with_stroke(Some(theme.players[0]))
+            .with_fill(Some(theme.background))
             .translate(Coord::new((w1_4 - 12) / 2, (HEIGHT as i32 - 12) / 2))
             .into_iter().chain(
             Font12x16::render_str("2")
-            .with_stroke(Some(C_PLAYER_B))
-            .with_fill(Some(C_BLACK))
+            .with_stroke(Some(theme.players[1]))
+            .with_fill(Some(theme.background))
             .translate(Coord::new(w1_4 + (w1_4 - 12) / 2, (HEIGHT as i32 - 12) / 2))
             .into_iter()
             ).chain(
             Font12x16::render_str("3")
-            .with_stroke(Some(C_PLAYER_C))
-            .with_fill(Some(C_BLACK))
+            .with_stroke(Some(theme.players[2]))
+            .with_fill(Some(theme.background))
             .translate(Coord::new(w1_4*2 + (w1_4 - 12) / 2, (HEIGHT as i32 - 12) / 2))
             .into_iter()
             ).chain(
             Font12x16::render_str("4")
-            .with_stroke(Some(C_PLAYER_D))
-            .with_fill(Some(C_BLACK))
+            .with_stroke(Some(theme.players[3]))
+            .with_fill(Some(theme.background))
             .translate(Coord::new(w1_4*3 + (w1_4 - 12) / 2, (HEIGHT as i32 - 12) / 2))
             .into_iter()
     ));
+}
+
+/// Bots play demo rounds on random maps until the screen is touched.
+fn attract_mode<F: Framebuffer>(display: &mut LcdDisplay<F>, i2c_3: &mut I2C<I2C3>,
+                                rng: &mut Random, settings: &Settings, traces: &mut TracePool) {
+    let demo = Settings { theme: settings.theme, ..Settings::default() };
+    let theme = theme(demo.theme as usize);
+    loop {
+        let map = Map::builtin(rng.next_u32() as usize % MAPS.len());
+        let mut game = Game::new(&theme.players, demo, scoring_rule(0), map, rng, traces);
+        game.new_game(rng);
+        display.clear();
+        game.draw_arena(display);
+
+        let mut last_ticks = system_clock::ticks();
+        loop {
+            let ticks = system_clock::ticks();
+            let d_ticks = ticks - last_ticks;
+            if d_ticks < 3 {
+                continue;
+            }
+            last_ticks = system_clock::ticks();
+
+            if !touch::touches(i2c_3).unwrap().is_empty() {
+                return;
+            }
+            let inputs = bot_inputs(&game);
+            let state = game.step(rng, display, &inputs, d_ticks);
+            // on both long sides, people stand around the board
+            draw_text_top(display, "TOUCH TO PLAY", theme.background, theme.text);
+            draw_text_bottom(display, "TOUCH TO PLAY", theme.background, theme.text);
+            if let GameState::Finished = state {
+                text_above_mid(display, &game.winner_message(), theme.background, theme.text);
+                let shown = system_clock::ticks();
+                while system_clock::ticks() - shown < DEMO_RESULT {
+                    if !touch::touches(i2c_3).unwrap().is_empty() {
+                        return;
+                    }
+                }
+                break;
+            }
+        }
+    }
+}
+
+/// Shows the options side by side, four per row, and returns the index of the touched one.
+fn menu_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, options: &[&str], theme: &Theme)
+                  -> usize
+where
+    D: Drawing<GameColor>,
+{
+    let cols = options.len().min(4);
+    let rows = (options.len() + cols - 1) / cols;
+    let w = (WIDTH / cols) as i32;
+    let h = (HEIGHT / rows) as i32;
+    for (i, name) in options.iter().enumerate() {
+        let len = (name.len() * 12) as i32;
+        let (col, row) = ((i % cols) as i32, (i / cols) as i32);
+        display.draw(Font12x16::render_str(name)
+                .with_stroke(Some(theme.text))
+                .with_fill(Some(theme.background))
+                .translate(Coord::new(w * col + (w - len) / 2, h * row + (h - 12) / 2))
+                .into_iter());
+    }
+    loop {
+        for touch in &touch::touches(i2c_3).unwrap() {
+            let col = (touch.x as usize * cols / WIDTH).min(cols - 1);
+            let row = (touch.y as usize * rows / HEIGHT).min(rows - 1);
+            return (row * cols + col).min(options.len() - 1);
+        }
+    }
+}
+
+fn scoring_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, settings: &Settings) -> usize
+where
+    D: Drawing<GameColor>,
+{
+    let mut names = [""; SCORING_RULES];
+    for (i, name) in names.iter_mut().enumerate() {
+        *name = scoring_rule(i).name();
+    }
+    menu_select(display, i2c_3, &names, theme(settings.theme as usize))
+}
+
+fn team_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, settings: &mut Settings)
+where
+    D: Drawing<GameColor>,
+{
+    let mode = menu_select(display, i2c_3, &["Solo", "Teams", "Ghost teams"],
+                           theme(settings.theme as usize));
+    settings.teams = mode > 0;
+    settings.friendly_trails_passable = mode > 1;
+}
+
+fn arena_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, settings: &mut Settings)
+where
+    D: Drawing<GameColor>,
+{
+    let mode = menu_select(display, i2c_3, &["Classic", "Shrinking"],
+                           theme(settings.theme as usize));
+    settings.shrinking_border = mode == 1;
+}
+
+fn spawn_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, settings: &mut Settings)
+where
+    D: Drawing<GameColor>,
+{
+    let mode = menu_select(display, i2c_3, &["Random start", "Symmetric start"],
+                           theme(settings.theme as usize));
+    settings.symmetric_spawns = mode == 1;
+}
+
+fn theme_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, settings: &mut Settings)
+where
+    D: Drawing<GameColor>,
+{
+    let names: Vec<&str> = THEMES.iter().map(|t| t.name).collect();
+    settings.theme = menu_select(display, i2c_3, &names, theme(settings.theme as usize)) as u8;
+}
+
+/// Every player picks a profile, makes a new one or plays as a guest. Returns the profile
+/// names by player.
+fn profile_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, players: usize,
+                     profiles: &mut Profiles, theme: &Theme) -> Vec<Option<String>>
+where
+    D: Drawing<GameColor>,
+{
+    let mut chosen: Vec<Option<String>> = Vec::new();
+    for i in 0..players {
+        let order: Vec<usize> = profiles.for_seat(i as u8)
+                                        .into_iter()
+                                        .filter(|p| {
+                                            let name = &profiles.profiles[*p].name;
+                                            !chosen.iter().any(|c| c.as_ref() == Some(name))
+                                        })
+                                        .collect();
+        let pick = {
+            let mut names: Vec<&str> = order.iter()
+                                            .map(|p| profiles.profiles[*p].name.as_str())
+                                            .collect();
+            names.push("New");
+            names.push("Guest");
+            let prompt = format!("Player {}: who are you?", player_letter(i));
+            draw_seat_text(display, i, &prompt, theme.background, theme.players[i]);
+            menu_select(display, i2c_3, &names, theme)
+        };
+        wait_release(i2c_3);
+        display.clear();
+        let name = if pick < order.len() {
+            let profile = &mut profiles.profiles[order[pick]];
+            profile.seat = i as u8;
+            Some(profile.name.clone())
+        } else if pick == order.len() {
+            // a name that is taken would reset that profile or count its rounds twice
+            let taken: Vec<String> = profiles.profiles.iter()
+                                              .map(|p| p.name.clone())
+                                              .chain(chosen.iter().filter_map(|c| c.clone()))
+                                              .collect();
+            let name = enter_name(display, i2c_3, Seat::from_index(i), taken, theme);
+            wait_release(i2c_3);
+            display.clear();
+            let color = color_select(display, i2c_3, i, theme);
+            wait_release(i2c_3);
+            display.clear();
+            profiles.add(Profile::new(&name, color, i as u8));
+            Some(name)
+        } else {
+            None
+        };
+        chosen.push(name);
+    }
+    chosen
+}
+
+/// The name is typed on a keyboard facing the seat, it has to differ from the `taken` ones.
+fn enter_name<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, seat: Seat, taken: Vec<String>,
+                 theme: &Theme) -> String
+where
+    D: Drawing<GameColor>,
+{
+    let mut keyboard = Keyboard::new(seat, taken);
+    loop {
+        let touches = touch::touches(i2c_3).unwrap();
+        let touch = touches.first().map(|t| Coord::new(i32::from(t.x), i32::from(t.y)));
+        if keyboard.touch(touch) {
+            return keyboard.text;
+        }
+        keyboard.draw(display, theme);
+    }
+}
+
+/// Returns the index of the touched player color.
+fn color_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, player: usize, theme: &Theme) -> u8
+where
+    D: Drawing<GameColor>,
+{
+    let w = (WIDTH / MAX_PLAYERS) as i32;
+    for (i, color) in theme.players.iter().enumerate() {
+        let x = i as i32 * w;
+        display.draw(Rect::new(Coord::new(x + 16, 32), Coord::new(x + w - 16, HEIGHT as i32 - 32))
+                .with_stroke(Some(*color))
+                .with_fill(Some(*color))
+                .into_iter());
+    }
+    draw_seat_text(display, player, "Pick your color", theme.background, theme.text);
     loop {
         for touch in &touch::touches(i2c_3).unwrap() {
-            if (touch.x as usize) < WIDTH / 4 {
-                return 1;
-            } else if (touch.x as usize) < WIDTH / 2 {
-                return 2;
-            } else if (touch.x as usize) < 3* WIDTH / 4 {
-                return 3;
-            } else {
-                return 4;
-            } 
+            return (touch.x as usize * MAX_PLAYERS / WIDTH).min(MAX_PLAYERS - 1) as u8;
+        }
+    }
+}
+
+/// Player colors with the favourite color of each profile, unless an earlier player has it.
+/// Team games keep the team colors.
+fn profile_colors(theme: &Theme, teams: bool, profiles: &Profiles, chosen: &[Option<String>])
+                  -> [GameColor; MAX_PLAYERS] {
+    let mut colors = *theme.player_colors(teams);
+    if teams {
+        return colors;
+    }
+    let mut claimed = [false; MAX_PLAYERS];
+    for (i, name) in chosen.iter().enumerate().take(MAX_PLAYERS) {
+        let profile = match name.as_ref().and_then(|n| profiles.find(n)) {
+            Some(p) => &profiles.profiles[p],
+            None => continue,
+        };
+        let wanted = theme.players[profile.color as usize % MAX_PLAYERS];
+        if let Some(j) = colors.iter().position(|c| *c == wanted) {
+            if !claimed[j] {
+                colors.swap(i, j);
+                claimed[i] = true;
+            }
+        }
+    }
+    colors
+}
+
+/// Handicaps of the players, all default unless they are switched on for the match.
+fn handicap_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, names: &[String], theme: &Theme)
+                      -> Vec<Handicap>
+where
+    D: Drawing<GameColor>,
+{
+    let mut handicaps = vec![Handicap::default(); names.len()];
+    if menu_select(display, i2c_3, &["No handicaps", "Handicaps"], theme) == 0 {
+        return handicaps;
+    }
+    for (i, name) in names.iter().enumerate() {
+        wait_release(i2c_3);
+        display.clear();
+        handicaps[i] = edit_handicap(display, i2c_3, i, name, theme);
+    }
+    handicaps
+}
+
+/// One row per modifier showing its value, and a row to confirm.
+fn edit_handicap<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, player: usize, name: &str,
+                    theme: &Theme) -> Handicap
+where
+    D: Drawing<GameColor>,
+{
+    const ROWS: usize = 5;
+    let h = (HEIGHT / ROWS) as i32;
+    let mut levels = [0_usize; ROWS - 1];
+    draw_seat_text(display, player, &format!("Handicap for {}", name), theme.background,
+                   theme.players[player]);
+    loop {
+        let handicap = Handicap {
+            speed: HANDICAP_FACTORS[levels[0]],
+            turn: HANDICAP_FACTORS[levels[1]],
+            radius: HANDICAP_FACTORS[levels[2]],
+            pickup: PICKUP_BONUSES[levels[3]],
+        };
+        let labels = [
+            format!("Speed   {:>4.0}%", handicap.speed * 100.0),
+            format!("Turn    {:>4.0}%", handicap.turn * 100.0),
+            format!("Radius  {:>4.0}%", handicap.radius * 100.0),
+            format!("Pickup  {:>+5.0}", handicap.pickup),
+            String::from("Done"),
+        ];
+        for (row, label) in labels.iter().enumerate() {
+            let len = (label.len() * 12) as i32;
+            display.draw(Font12x16::render_str(label)
+                    .with_stroke(Some(if row < ROWS - 1 { theme.text } else { theme.accent }))
+                    .with_fill(Some(theme.background))
+                    .translate(Coord::new((WIDTH as i32 - len) / 2, h * row as i32 + (h - 16) / 2))
+                    .into_iter());
+        }
+        let row = loop {
+            if let Some(touch) = touch::touches(i2c_3).unwrap().first() {
+                break (touch.y as usize * ROWS / HEIGHT).min(ROWS - 1);
+            }
+        };
+        if row == ROWS - 1 {
+            return handicap;
+        }
+        let steps = if row == ROWS - 2 { PICKUP_BONUSES.len() } else { HANDICAP_FACTORS.len() };
+        levels[row] = (levels[row] + 1) % steps;
+        wait_release(i2c_3);
+    }
+}
+
+/// Returns the chosen map, `None` if a new one should be drawn in the editor.
+fn map_select<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, custom_maps: &CustomMaps,
+                 theme: &Theme) -> Option<Map>
+where
+    D: Drawing<GameColor>,
+{
+    let mut maps: Vec<Map> = (0..MAPS.len()).map(Map::builtin).collect();
+    maps.extend(custom_maps.parsed());
+    let mut names: Vec<&str> = maps.iter().map(|m| m.name.as_str()).collect();
+    names.push("Editor");
+    let i = menu_select(display, i2c_3, &names, theme);
+    maps.get(i).cloned()
+}
+
+fn edit_map<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, custom_maps: &CustomMaps,
+               theme: &Theme) -> Map
+where
+    D: Drawing<GameColor>,
+{
+    let mut editor = Editor::new(Map::default(), WIDTH as i32, HEIGHT as i32);
+    loop {
+        let touches = touch::touches(i2c_3).unwrap();
+        let touch = touches.first().map(|t| Coord::new(i32::from(t.x), i32::from(t.y)));
+        if editor.touch(touch) {
+            break;
         }
+        editor.draw(display, theme);
     }
+    wait_release(i2c_3);
+    display.clear();
+    // a custom map with the name of another one would replace it
+    let taken: Vec<String> = (0..MAPS.len()).map(|i| Map::builtin(i).name)
+                                            .chain(custom_maps.parsed().into_iter().map(|m| m.name))
+                                            .collect();
+    let mut map = editor.map;
+    map.name = enter_name(display, i2c_3, Seat::Bottom, taken, theme);
+    map
 }
 
-fn ready_screen<D>(display: &mut D, cooldown: i32)
+fn wait_release(i2c_3: &mut I2C<I2C3>) {
+    while !touch::touches(i2c_3).unwrap().is_empty() {}
+}
+
+/// Counts down to the round, the arrows show where every curve starts and where it heads.
+fn ready_screen<D>(display: &mut D, game: &Game, cooldown: i32)
 where
     D: Drawing<GameColor>,
 {
@@ -257,11 +729,151 @@ where
     let mut passed = (system_clock::ticks() - start_tm) as i32;
     while passed < cooldown {
         huge_text_mid(display, &format!("BE READY! FUN STARTS IN {} SECONDS!!", (cooldown - passed) / 100),
-                      C_BLACK, C_PLAYER_A);
+                      game.theme().background, game.theme().accent);
+        // over the text, a start point can be right behind it
+        for (spawn, p) in game.spawns().iter().zip(game.players.iter()) {
+            spawn.draw_arrow(display, p.curve.color);
+        }
         passed = (system_clock::ticks() - start_tm) as i32;
     }
 }
 
+pub fn player_letter(i: usize) -> char {
+    (b'A' + i as u8) as char
+}
+
+/// Score line of a player, drawn while the round runs so it doesn't allocate.
+fn hud_text(game: &Game, i: usize) -> heapless::String<U64> {
+    let p = &game.players[i];
+    let unit = game.scoring.unit();
+    let mut text = heapless::String::new();
+    let _ = match p.team {
+        Some(t) => write!(text, "<--- {}: {:04} {}  {}: {:04} --->", p.name,
+                          p.score, unit, team_name(t), game.team_score(t)),
+        None => write!(text, "<--- {}: {:04} {}  --->", p.name, p.score, unit),
+    };
+    text
+}
+
+/// Draws the score lines of all players and remembers the scores they show. A team score
+/// changes the lines of both members, so all of them are drawn.
+fn draw_hud<D>(display: &mut D, game: &Game, colors: &[GameColor],
+               drawn_scores: &mut [i32; MAX_PLAYERS])
+where D: Drawing<GameColor> {
+    for (i, p) in game.players.iter().enumerate() {
+        drawn_scores[i] = p.score;
+        draw_seat_text(display, i, &hud_text(game, i), game.theme().background, colors[i]);
+    }
+}
+
+fn death_message(game: &Game, victim: usize, cause: DeathCause) -> String {
+    let v = &game.players[victim].name;
+    let name = |k: usize| &game.players[k].name;
+    match cause {
+        DeathCause::CutOffBy(k) => format!("{} was cut off by {}", v, name(k)),
+        DeathCause::Suicide => format!("{} ran into its own trace", v),
+        DeathCause::Border => format!("{} hit the border", v),
+        DeathCause::HeadOn(k) => format!("{} crashed head-on into {}", v, name(k)),
+        DeathCause::Wall => format!("{} hit a wall", v),
+    }
+}
+
+/// Standings of the round until every player tapped their side of the screen, or the time
+/// is up.
+fn round_summary<D>(display: &mut D, i2c_3: &mut I2C<I2C3>, game: &Game)
+where
+    D: Drawing<GameColor>,
+{
+    let theme = game.theme();
+    let title = game.winner_message();
+    let len = (title.len() * 12) as i32;
+    display.draw(Font12x16::render_str(&title)
+            .with_stroke(Some(theme.text))
+            .with_fill(Some(theme.background))
+            .translate(Coord::new((WIDTH as i32 - len) / 2, 16))
+            .into_iter());
+    let placements = game.placements();
+    let row_y = |row: usize| 56 + row as i32 * 40;
+    for (row, (i, place)) in placements.iter().enumerate() {
+        let p = &game.players[*i];
+        let text = format!("{}. {}  {:+} {}  = {}", place, p.name,
+                           p.round_score, game.scoring.unit(), p.score);
+        display.draw(Font12x16::render_str(&text)
+                .with_stroke(Some(p.curve.color))
+                .with_fill(Some(theme.background))
+                .translate(Coord::new(40, row_y(row)))
+                .into_iter());
+        let cause = match p.death {
+            Some(cause) => death_message(game, *i, cause),
+            None => String::from("survived"),
+        };
+        display.draw(Font6x8::render_str(&cause)
+                .with_stroke(Some(theme.text))
+                .with_fill(Some(theme.background))
+                .translate(Coord::new(64, row_y(row) + 20))
+                .into_iter());
+        // right-aligned, so it stays clear of the death message
+        let stats = p.stats.describe();
+        let stats_x = WIDTH as i32 - 8 - (stats.len() * 6) as i32;
+        display.draw(Font6x8::render_str(&stats)
+                .with_stroke(Some(theme.text))
+                .with_fill(Some(theme.background))
+                .translate(Coord::new(stats_x, row_y(row) + 20))
+                .into_iter());
+        if !p.handicap.is_none() {
+            display.draw(Font6x8::render_str(&format!("handicap: {}", p.handicap.describe()))
+                    .with_stroke(Some(theme.text))
+                    .with_fill(Some(theme.background))
+                    .translate(Coord::new(64, row_y(row) + 30))
+                    .into_iter());
+        }
+    }
+
+    // fingers still on the screen from steering don't count
+    wait_release(i2c_3);
+    let mut confirmed = [false; MAX_PLAYERS];
+    let start = system_clock::ticks();
+    loop {
+        let passed = system_clock::ticks() - start;
+        if passed >= SUMMARY_TIMEOUT || confirmed.iter().take(game.players.len()).all(|c| *c) {
+            return;
+        }
+        let touches: Vec<Coord> = touch::touches(i2c_3).unwrap()
+                                                       .iter()
+                                                       .map(|t| Coord::new(i32::from(t.x),
+                                                                           i32::from(t.y)))
+                                                       .collect();
+        for (row, (i, _)) in placements.iter().enumerate() {
+            if game.players[*i].touch_input(&touches) != PlayerInput::None {
+                confirmed[*i] = true;
+            }
+            if confirmed[*i] {
+                display.draw(Font12x16::render_str("OK")
+                        .with_stroke(Some(theme.accent))
+                        .with_fill(Some(theme.background))
+                        .translate(Coord::new(WIDTH as i32 - 64, row_y(row)))
+                        .into_iter());
+            }
+        }
+        let hint = format!("Tap your side to continue ({:2})",
+                           (SUMMARY_TIMEOUT - passed) / 100 + 1);
+        draw_text_bottom(display, &hint, theme.background, theme.text);
+    }
+}
+
+/// Draws the text along the edge of the seat of player `i`, readable from there.
+fn draw_seat_text<D>(display: &mut D, i: usize, text: &str, fill_color: GameColor,
+                     text_color: GameColor)
+where D: Drawing<GameColor> {
+    match i {
+        0 => draw_text_right(display, text, fill_color, text_color),
+        1 => draw_text_left(display, text, fill_color, text_color),
+        2 => draw_text_top(display, text, fill_color, text_color),
+        3 => draw_text_bottom(display, text, fill_color, text_color),
+        _ => {},
+    }
+}
+
 fn text_above_mid<'a, D>(display: &mut D, text: &'a str, fill_color: GameColor, 
                          text_color: GameColor)
 where 
@@ -337,12 +949,17 @@ where D: Drawing<GameColor> {
                 .into_iter());
 }
 
-fn get_rand_num(rnd: &mut Rng) -> u32 {
-    loop {
-        match rnd.poll_and_get() {
-            Err(_) => {}
-            Ok(num) => {
-                break num;
+/// The RNG peripheral as the source of random numbers for the game.
+struct HardwareRng(Rng);
+
+impl Random for HardwareRng {
+    fn next_u32(&mut self) -> u32 {
+        loop {
+            match self.0.poll_and_get() {
+                Err(_) => {}
+                Ok(num) => {
+                    break num;
+                }
             }
         }
     }
@@ -357,15 +974,18 @@ fn SysTick() {
 }
 
 // define what happens in an Out Of Memory (OOM) condition
+//
+// Shouldn't happen: the traces live in `TRACES` and a full trace ends the round as "Arena
+// full", so the heap only holds a few menu and summary strings. If it does happen anyway,
+// restart instead of hanging with a frozen screen.
 #[alloc_error_handler]
-fn rust_oom(_: AllocLayout) -> ! {
-    println!("OOM!!");
-    loop {}
+fn rust_oom(layout: AllocLayout) -> ! {
+    println!("Out of memory allocating {} bytes, resetting", layout.size());
+    cortex_m::peripheral::SCB::system_reset2()
 }
 
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    use core::fmt::Write;
     use cortex_m::asm;
     use cortex_m_semihosting::hio;
     println!("PANIC");